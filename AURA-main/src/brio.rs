@@ -0,0 +1,160 @@
+//! BRIO: a dependency-free generic byte codec
+//!
+//! Serves as the "does this text have any compressible structure at all"
+//! candidate in `Compressor`'s arbitration: unlike the template and
+//! dictionary codecs it needs no prior training or registered patterns, so
+//! it always has something to offer against text a template never matches
+//! and the FSST table was never trained on. A straightforward LZ77 pass -
+//! emit runs of literal bytes interleaved with back-references into a
+//! sliding window - favoring the literal, general-purpose fallback it was
+//! designed to be over squeezing out the last few bytes.
+
+/// Matches shorter than this cost more to encode (4-byte reference) than
+/// they save, so they are left as literals.
+const MIN_MATCH_LEN: usize = 4;
+/// Longest back-reference a single match token can encode.
+const MAX_MATCH_LEN: usize = 255 + MIN_MATCH_LEN;
+/// How far back a match may point; bounds both the search cost and the
+/// 2-byte offset field.
+const WINDOW_SIZE: usize = u16::MAX as usize;
+
+/// Compress `data` into a stream of literal-run and back-reference tokens.
+///
+/// Token layout:
+/// - `0x00`, `len: u16 BE`, `len` raw bytes -- a literal run.
+/// - `0x01`, `offset: u16 BE`, `extra_len: u8` -- a back-reference `offset`
+///   bytes behind the cursor, `MIN_MATCH_LEN + extra_len` bytes long.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut literal_run = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        match longest_match(data, pos) {
+            Some((offset, len)) => {
+                flush_literal_run(&mut out, &mut literal_run);
+                out.push(0x01);
+                out.extend_from_slice(&(offset as u16).to_be_bytes());
+                out.push((len - MIN_MATCH_LEN) as u8);
+                pos += len;
+            }
+            None => {
+                literal_run.push(data[pos]);
+                pos += 1;
+            }
+        }
+    }
+    flush_literal_run(&mut out, &mut literal_run);
+
+    out
+}
+
+fn flush_literal_run(out: &mut Vec<u8>, literal_run: &mut Vec<u8>) {
+    if literal_run.is_empty() {
+        return;
+    }
+    out.push(0x00);
+    out.extend_from_slice(&(literal_run.len() as u16).to_be_bytes());
+    out.extend_from_slice(literal_run);
+    literal_run.clear();
+}
+
+/// Find the longest match for the bytes starting at `pos` within the
+/// preceding `WINDOW_SIZE` bytes, scanning candidate start positions
+/// linearly (data volumes here are single chat messages, not a corpus, so a
+/// hash-chain index would be overhead without payoff).
+fn longest_match(data: &[u8], pos: usize) -> Option<(usize, usize)> {
+    if pos + MIN_MATCH_LEN > data.len() {
+        return None;
+    }
+
+    let window_start = pos.saturating_sub(WINDOW_SIZE);
+    let max_len = MAX_MATCH_LEN.min(data.len() - pos);
+    let mut best: Option<(usize, usize)> = None;
+
+    for start in window_start..pos {
+        let mut len = 0;
+        while len < max_len && data[start + len] == data[pos + len] {
+            len += 1;
+        }
+        if len >= MIN_MATCH_LEN && best.map_or(true, |(_, best_len)| len > best_len) {
+            best = Some((pos - start, len));
+        }
+    }
+
+    best
+}
+
+/// Expand a token stream produced by `compress`.
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let tag = data[pos];
+        pos += 1;
+        match tag {
+            0x00 => {
+                let len = read_u16(data, pos)? as usize;
+                pos += 2;
+                let end = pos + len;
+                let literal = data
+                    .get(pos..end)
+                    .ok_or_else(|| "truncated literal run".to_string())?;
+                out.extend_from_slice(literal);
+                pos = end;
+            }
+            0x01 => {
+                let offset = read_u16(data, pos)? as usize;
+                pos += 2;
+                let extra_len = *data.get(pos).ok_or_else(|| "truncated match token".to_string())?;
+                pos += 1;
+                let len = MIN_MATCH_LEN + extra_len as usize;
+
+                if offset == 0 || offset > out.len() {
+                    return Err(format!("match offset {} out of range", offset));
+                }
+                let start = out.len() - offset;
+                for i in 0..len {
+                    let byte = out[start + i];
+                    out.push(byte);
+                }
+            }
+            other => return Err(format!("unknown token tag {}", other)),
+        }
+    }
+
+    Ok(out)
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Result<u16, String> {
+    let bytes = data
+        .get(pos..pos + 2)
+        .ok_or_else(|| "truncated length field".to_string())?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_repetitive_text() {
+        let text = b"the quick brown fox the quick brown fox the quick brown fox";
+        let encoded = compress(text);
+        assert_eq!(decompress(&encoded).unwrap(), text);
+        assert!(encoded.len() < text.len());
+    }
+
+    #[test]
+    fn round_trips_text_with_no_repetition() {
+        let text = b"abcdefghijklmnopqrstuvwxyz";
+        let encoded = compress(text);
+        assert_eq!(decompress(&encoded).unwrap(), text);
+    }
+
+    #[test]
+    fn round_trips_empty_input() {
+        assert_eq!(decompress(&compress(b"")).unwrap(), Vec::<u8>::new());
+    }
+}
@@ -1,10 +1,27 @@
 //! Client SDK implementation
 
-use crate::{Compressor, DecompressionMetadata, Result};
+use crate::{CompressionMethod, Compressor, DecompressionMetadata, HashAlgorithm, RegistryChange, Result, TemplateInfo};
 use std::collections::HashMap;
 
+/// Methods every `ClientSDK` can decode unless told otherwise. A client
+/// that advertises this set as-is can read anything `Compressor::compress`
+/// can currently produce; an older client would instead pass a narrower
+/// list to `ClientSDK::new` so `ServerSDK` knows not to hand it a method
+/// it was built before.
+const ALL_METHODS: &[CompressionMethod] = &[
+    CompressionMethod::BinarySemantic,
+    CompressionMethod::AuraLite,
+    CompressionMethod::Brio,
+    CompressionMethod::AuraLiteV2,
+    CompressionMethod::AuraFsst,
+    CompressionMethod::Uncompressed,
+];
+
 pub struct ClientSDK {
     compressor: Compressor,
+    /// Advertised during the handshake so a peer `ServerSDK` only emits
+    /// frames this client can decode.
+    supported_methods: Vec<CompressionMethod>,
 }
 
 impl ClientSDK {
@@ -12,7 +29,18 @@ impl ClientSDK {
         template_store_path: Option<String>,
         extra_templates: Option<HashMap<u32, String>>,
     ) -> Self {
-        let mut compressor = Compressor::new(true, template_store_path, false, None, None);
+        Self::with_supported_methods(template_store_path, extra_templates, ALL_METHODS.to_vec())
+    }
+
+    /// Like `new`, but advertises a caller-chosen set of decodable methods
+    /// instead of everything this build knows about — e.g. an older client
+    /// built before a codec was added should list only what it can parse.
+    pub fn with_supported_methods(
+        template_store_path: Option<String>,
+        extra_templates: Option<HashMap<u32, String>>,
+        supported_methods: Vec<CompressionMethod>,
+    ) -> Self {
+        let mut compressor = Compressor::new(true, template_store_path, false, None, None, None);
 
         if let Some(templates) = extra_templates {
             for (id, pattern) in templates {
@@ -20,7 +48,14 @@ impl ClientSDK {
             }
         }
 
-        Self { compressor }
+        Self { compressor, supported_methods }
+    }
+
+    /// Methods this client is prepared to decode, for the server-side
+    /// handshake; always includes `Uncompressed` implicitly on the server
+    /// side regardless of what's returned here.
+    pub fn supported_methods(&self) -> &[CompressionMethod] {
+        &self.supported_methods
     }
 
     pub fn decode_payload(
@@ -28,22 +63,11 @@ impl ClientSDK {
         payload: &[u8],
         return_metadata: bool,
     ) -> Result<(String, Option<DecompressionMetadata>)> {
-        let text = self.compressor.decompress(payload)?;
-
         if return_metadata {
-            let method = if !payload.is_empty() {
-                crate::CompressionMethod::from_byte(payload[0])?.as_str().to_string()
-            } else {
-                "unknown".to_string()
-            };
-
-            let metadata = DecompressionMetadata {
-                method,
-                template_ids: vec![],
-            };
-
+            let (text, metadata) = self.compressor.decompress_with_metadata(payload)?;
             Ok((text, Some(metadata)))
         } else {
+            let text = self.compressor.decompress(payload)?;
             Ok((text, None))
         }
     }
@@ -53,15 +77,43 @@ impl ClientSDK {
         text: &str,
         template_id: Option<u32>,
         slots: Option<Vec<String>>,
+        integrity: Option<HashAlgorithm>,
     ) -> Result<(Vec<u8>, crate::CompressionMethod, crate::CompressionMetadata)> {
-        self.compressor.compress(text, template_id, slots)
+        self.compressor.compress(text, template_id, slots, integrity)
+    }
+
+    /// Like `compress`, but fills the template's slots by name instead of
+    /// position — see `Compressor::compress_named`.
+    pub fn compress_named(
+        &self,
+        template_id: u32,
+        named_slots: HashMap<String, String>,
+        integrity: Option<HashAlgorithm>,
+    ) -> Result<(Vec<u8>, crate::CompressionMethod, crate::CompressionMetadata)> {
+        self.compressor.compress_named(template_id, named_slots, integrity)
     }
 
     pub fn register_template(&mut self, template_id: u32, pattern: String) {
         self.compressor.register_template(template_id, pattern);
     }
 
-    pub fn list_templates(&self) -> HashMap<u32, String> {
+    pub fn list_templates(&self) -> HashMap<u32, TemplateInfo> {
         self.compressor.list_templates()
     }
+
+    /// The template registry version this client currently holds.
+    pub fn template_registry_version(&self) -> u32 {
+        self.compressor.template_registry_version()
+    }
+
+    /// Every registry change since `since_version`, to send to a server so
+    /// it can catch its own registry up without resending the whole table.
+    pub fn export_template_delta(&self, since_version: u32) -> Vec<RegistryChange> {
+        self.compressor.export_template_delta(since_version)
+    }
+
+    /// Apply a delta received from a server's `export_template_delta`.
+    pub fn import_template_delta(&self, changes: &[RegistryChange]) {
+        self.compressor.import_template_delta(changes);
+    }
 }
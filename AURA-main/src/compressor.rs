@@ -1,15 +1,95 @@
 //! Core compression implementation
 
-use crate::{AuraError, CompressionMetadata, CompressionMethod, Result};
+use crate::{AuraError, CompressionMetadata, CompressionMethod, DecompressionMetadata, HashAlgorithm, Result, TemplateInfo};
+use crate::brio;
+use crate::fsst::SymbolTable;
+use crate::metadata::MetadataIndex;
+use crate::miner::TemplateMiner;
+use crate::rules::RuleEngine;
 use crate::template_library::TemplateLibrary;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Version of the payload container itself (header layout), independent of
+/// the template-library/dictionary version carried inside it. Bump this if
+/// the header layout ever changes shape.
+const FORMAT_VERSION: u8 = 1;
+
+/// Header value of the integrity-tag byte meaning "no trailer appended".
+/// Kept well clear of `CompressionMethod`'s byte range (0x00-0x04, 0xFF) and
+/// `HashAlgorithm`'s (0x10+) so the three tags can never be confused even if
+/// a caller reads the wrong header offset.
+const INTEGRITY_NONE: u8 = 0x08;
+
+/// Computes the digest `wrap` appends as the integrity trailer, and that
+/// `decode_inner` recomputes over the decompressed text to verify it.
+fn compute_digest(algorithm: HashAlgorithm, text: &str) -> Vec<u8> {
+    match algorithm {
+        HashAlgorithm::Md5 => md5::compute(text.as_bytes()).0.to_vec(),
+        HashAlgorithm::Sha1 => {
+            use sha1::{Digest, Sha1};
+            Sha1::digest(text.as_bytes()).to_vec()
+        }
+        HashAlgorithm::Sha256 => {
+            use sha2::{Digest, Sha256};
+            Sha256::digest(text.as_bytes()).to_vec()
+        }
+    }
+}
+
+/// A candidate encoding considered by `Compressor::compress`'s arbitration:
+/// every available method is tried and the smallest wins, with a margin of
+/// preference for a semantic encoding over a same-size generic one.
+struct Candidate {
+    method: CompressionMethod,
+    /// Template-library/dictionary version the candidate's body was encoded
+    /// against, or 0 for methods with no such dependency.
+    version: u32,
+    body: Vec<u8>,
+    template_ids: Vec<u32>,
+    /// Lazily-decodable index of template substitutions, written ahead of
+    /// the body so `Compressor::peek_metadata` can read it without touching
+    /// `body` at all. Empty for every non-semantic candidate.
+    metadata_index: MetadataIndex,
+    /// Slot values `semantic_candidate` auto-extracted by matching the
+    /// input against the library, surfaced in `CompressionMetadata` so a
+    /// caller can see what was inferred. `None` for an explicit
+    /// template_id/slots pair or a non-semantic candidate.
+    matched_slots: Option<Vec<String>>,
+}
+
+impl Candidate {
+    fn total_len(&self) -> usize {
+        HEADER_LEN + self.metadata_index.encode().len() + self.body.len()
+    }
+}
+
+/// `format_version` + `method` + `version` (u32 BE) + integrity tag.
+const HEADER_LEN: usize = 1 + 1 + 4 + 1;
+
+/// A decoded payload, before the caller-facing `decompress` /
+/// `decompress_with_metadata` split decides whether an integrity mismatch
+/// should be a hard error.
+struct DecodedPayload {
+    text: String,
+    method: CompressionMethod,
+    template_ids: Vec<u32>,
+    /// `Some((algorithm, verified))` when the payload carried an integrity
+    /// trailer; `None` for a payload compressed without one.
+    integrity: Option<(HashAlgorithm, bool)>,
+}
+
 pub struct Compressor {
     enable_aura: bool,
-    template_library: TemplateLibrary,
+    template_library: RefCell<TemplateLibrary>,
+    miner: RefCell<TemplateMiner>,
+    fsst_table: Option<SymbolTable>,
     enable_audit_logging: bool,
     session_id: Option<String>,
     user_id: Option<String>,
+    rule_engine: RuleEngine,
     aura_preference_margin: f64,
 }
 
@@ -20,30 +100,124 @@ impl Compressor {
         enable_audit_logging: bool,
         session_id: Option<String>,
         user_id: Option<String>,
+        rule_db_path: Option<String>,
     ) -> Self {
         let mut template_library = TemplateLibrary::new();
+        let mut fsst_table = None;
 
         if let Some(path) = template_store_path {
             if let Err(e) = template_library.load_from_file(&path) {
                 log::warn!("Failed to load template store: {}", e);
             }
+            match Self::load_fsst_table(&path) {
+                Ok(table) => fsst_table = table,
+                Err(e) => log::warn!("Failed to load AuraFsst symbol table: {}", e),
+            }
         }
 
+        let rule_engine = match rule_db_path {
+            Some(path) => RuleEngine::load_from_file(&path).unwrap_or_else(|e| {
+                log::warn!("Failed to load rule database: {}", e);
+                RuleEngine::empty()
+            }),
+            None => RuleEngine::empty(),
+        };
+
         Self {
             enable_aura,
-            template_library,
+            template_library: RefCell::new(template_library),
+            miner: RefCell::new(TemplateMiner::new()),
+            fsst_table,
             enable_audit_logging,
             session_id,
             user_id,
+            rule_engine,
             aura_preference_margin: 0.1,
         }
     }
 
+    /// Train the AuraFsst symbol table over a corpus of prior messages.
+    pub fn train_fsst(&mut self, samples: &[String]) {
+        self.fsst_table = Some(SymbolTable::train(samples));
+    }
+
+    /// Persist the trained symbol table alongside the template store, under
+    /// the `fsst_table` key of the same JSON file `load_fsst_table` reads.
+    pub fn save_fsst_table(&self, path: &str) -> Result<()> {
+        let table = match &self.fsst_table {
+            Some(table) => table,
+            None => return Ok(()),
+        };
+
+        let mut data: serde_json::Value = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(_) => serde_json::json!({}),
+        };
+        data["fsst_table"] = serde_json::to_value(table)?;
+        fs::write(path, serde_json::to_string_pretty(&data)?)?;
+        Ok(())
+    }
+
+    fn load_fsst_table(path: &str) -> Result<Option<SymbolTable>> {
+        let content = fs::read_to_string(path)?;
+        let data: serde_json::Value = serde_json::from_str(&content)?;
+        match data.get("fsst_table") {
+            Some(value) => Ok(Some(serde_json::from_value(value.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Encode `text` with every available method and emit the smallest
+    /// payload, so the result is never worse than the plain `Uncompressed`
+    /// encoding. A binary-semantic candidate is only preferred over a
+    /// smaller generic one (Brio, AuraFsst, Uncompressed) when it's within
+    /// `aura_preference_margin` of it and no larger than Uncompressed, since
+    /// a semantic match carries more value than raw byte count alone (it's
+    /// what `decode_payload`'s metadata side-channel relies on). AuraFsst
+    /// competes with the other generic candidates on size alone.
+    ///
+    /// `template_id: None` asks `semantic_candidate` to auto-select a
+    /// template by matching `text` against the library instead of using a
+    /// caller-supplied one; the chosen id and extracted slots come back in
+    /// `CompressionMetadata::template_ids`/`matched_slots` and compression
+    /// falls back to a generic candidate when nothing matches.
+    ///
+    /// `integrity` is opt-in: when `Some`, a digest of `text` is appended to
+    /// the payload as a trailer and checked back on decode (see
+    /// `decompress`); `None` produces a payload with no trailer at all.
     pub fn compress(
         &self,
         text: &str,
         template_id: Option<u32>,
         slots: Option<Vec<String>>,
+        integrity: Option<HashAlgorithm>,
+    ) -> Result<(Vec<u8>, CompressionMethod, CompressionMetadata)> {
+        self.compress_candidates(text, template_id, slots, None, integrity)
+    }
+
+    /// Like `compress`, but only arbitrates among methods the peer has
+    /// advertised support for (see `ClientSDK`'s handshake). `Uncompressed`
+    /// is always implicitly allowed regardless of `allowed_methods`, since
+    /// every decoder can read it; this guarantees a result even when the
+    /// peer supports none of the candidates that would otherwise win.
+    pub fn compress_for_peer(
+        &self,
+        text: &str,
+        template_id: Option<u32>,
+        slots: Option<Vec<String>>,
+        allowed_methods: &[CompressionMethod],
+        integrity: Option<HashAlgorithm>,
+    ) -> Result<(Vec<u8>, CompressionMethod, CompressionMetadata)> {
+        self.compress_candidates(text, template_id, slots, Some(allowed_methods), integrity)
+    }
+
+    fn compress_candidates(
+        &self,
+        text: &str,
+        template_id: Option<u32>,
+        slots: Option<Vec<String>>,
+        allowed_methods: Option<&[CompressionMethod]>,
+        integrity: Option<HashAlgorithm>,
     ) -> Result<(Vec<u8>, CompressionMethod, CompressionMetadata)> {
         let original_size = text.len();
         let timestamp = SystemTime::now()
@@ -51,70 +225,201 @@ impl Compressor {
             .unwrap()
             .as_secs();
 
-        // Try binary semantic first
-        if let Some(tid) = template_id {
-            if let Some(slot_list) = slots {
-                let payload = self.compress_binary_semantic(tid, &slot_list)?;
-                let compressed_size = payload.len();
-                let ratio = original_size as f64 / compressed_size as f64;
-
-                return Ok((
-                    payload,
-                    CompressionMethod::BinarySemantic,
-                    CompressionMetadata {
-                        original_size,
-                        compressed_size,
-                        ratio,
-                        method: "binary_semantic".to_string(),
-                        template_ids: vec![tid],
-                        timestamp,
-                    },
-                ));
-            }
+        let is_allowed = |method: CompressionMethod| {
+            method == CompressionMethod::Uncompressed
+                || match allowed_methods {
+                    Some(allowed) => allowed.contains(&method),
+                    None => true,
+                }
+        };
+
+        let semantic = self
+            .semantic_candidate(text, template_id, slots)?
+            .filter(|candidate| is_allowed(candidate.method));
+
+        // No explicit or matched template; feed the message to the
+        // self-mining corpus so a recurring shape gets promoted to a real
+        // template on a future call.
+        if semantic.is_none() {
+            self.observe_for_mining(text);
+        }
+
+        let mut generic = vec![self.uncompressed_candidate(text), self.brio_candidate(text)];
+        if let Some(fsst) = self.fsst_candidate(text) {
+            generic.push(fsst);
         }
+        generic.retain(|candidate| is_allowed(candidate.method));
+
+        let best_generic_idx = generic
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, c)| c.total_len())
+            .map(|(i, _)| i)
+            .expect("the Uncompressed candidate is always allowed");
+
+        let uncompressed_len = generic
+            .iter()
+            .find(|c| c.method == CompressionMethod::Uncompressed)
+            .map(|c| c.total_len())
+            .expect("the Uncompressed candidate is always allowed");
+
+        let winner = match semantic {
+            Some(semantic) => {
+                let margin_threshold = (generic[best_generic_idx].total_len() as f64
+                    * (1.0 + self.aura_preference_margin))
+                    .min(uncompressed_len as f64);
+                if semantic.total_len() as f64 <= margin_threshold {
+                    semantic
+                } else {
+                    generic.swap_remove(best_generic_idx)
+                }
+            }
+            None => generic.swap_remove(best_generic_idx),
+        };
 
-        // Try template matching
-        if let Some((tid, slots)) = self.template_library.match_template(text) {
-            let payload = self.compress_binary_semantic(tid, &slots)?;
-            let compressed_size = payload.len();
-            let ratio = original_size as f64 / compressed_size as f64;
-
-            return Ok((
-                payload,
-                CompressionMethod::BinarySemantic,
-                CompressionMetadata {
-                    original_size,
-                    compressed_size,
-                    ratio,
-                    method: "binary_semantic".to_string(),
-                    template_ids: vec![tid],
-                    timestamp,
-                },
-            ));
-        }
-
-        // Fallback to AuraLite
-        let payload = self.compress_auralite(text)?;
+        let payload = Self::wrap(&winner, text, integrity);
         let compressed_size = payload.len();
         let ratio = original_size as f64 / compressed_size as f64;
 
         Ok((
             payload,
-            CompressionMethod::AuraLite,
+            winner.method,
             CompressionMetadata {
                 original_size,
                 compressed_size,
                 ratio,
-                method: "auralite".to_string(),
-                template_ids: vec![],
+                method: winner.method.as_str().to_string(),
+                template_ids: winner.template_ids,
                 timestamp,
+                matched_slots: winner.matched_slots,
             },
         ))
     }
 
-    fn compress_binary_semantic(&self, template_id: u32, slots: &[String]) -> Result<Vec<u8>> {
-        let mut payload = vec![0x00]; // Binary semantic method
-        payload.push(template_id as u8);
+    /// Build the binary-semantic candidate from an explicit `template_id` /
+    /// `slots` pair, or by matching `text` against the library otherwise.
+    fn semantic_candidate(
+        &self,
+        text: &str,
+        template_id: Option<u32>,
+        slots: Option<Vec<String>>,
+    ) -> Result<Option<Candidate>> {
+        let (tid, slot_list, matched_slots) = match (template_id, slots) {
+            (Some(tid), Some(slot_list)) => (tid, slot_list, None),
+            _ => match self.template_library.borrow().match_template(text) {
+                Some((tid, slot_list)) => {
+                    self.miner.borrow_mut().record_hit(tid);
+                    (tid, slot_list.clone(), Some(slot_list))
+                }
+                None => return Ok(None),
+            },
+        };
+
+        let (formatted, slot_offsets) = self
+            .template_library
+            .borrow()
+            .format_template_with_offsets(tid, &slot_list)?;
+        let template_refs = slot_offsets
+            .into_iter()
+            .map(|(offset, length)| crate::metadata::TemplateRef { template_id: tid, offset, length })
+            .collect();
+
+        Ok(Some(Candidate {
+            method: CompressionMethod::BinarySemantic,
+            version: self.template_library.borrow().version(),
+            body: Self::compress_binary_semantic(tid, &slot_list),
+            template_ids: vec![tid],
+            metadata_index: MetadataIndex { original_len: formatted.len() as u32, template_refs },
+            matched_slots,
+        }))
+    }
+
+    fn fsst_candidate(&self, text: &str) -> Option<Candidate> {
+        let table = self.fsst_table.as_ref()?;
+        Some(Candidate {
+            method: CompressionMethod::AuraFsst,
+            version: table.version(),
+            body: table.compress(text),
+            template_ids: vec![],
+            metadata_index: MetadataIndex::default(),
+            matched_slots: None,
+        })
+    }
+
+    fn brio_candidate(&self, text: &str) -> Candidate {
+        Candidate {
+            method: CompressionMethod::Brio,
+            version: 0,
+            body: brio::compress(text.as_bytes()),
+            template_ids: vec![],
+            metadata_index: MetadataIndex::default(),
+            matched_slots: None,
+        }
+    }
+
+    fn uncompressed_candidate(&self, text: &str) -> Candidate {
+        Candidate {
+            method: CompressionMethod::Uncompressed,
+            version: 0,
+            body: text.as_bytes().to_vec(),
+            template_ids: vec![],
+            metadata_index: MetadataIndex::default(),
+            matched_slots: None,
+        }
+    }
+
+    /// Wrap a candidate's body in the versioned container: format version,
+    /// method byte, then the template-library/dictionary version the body
+    /// was encoded against, so `decompress` can detect a payload produced
+    /// against an incompatible table instead of silently mis-expanding it.
+    /// Next comes the candidate's metadata index (see `crate::metadata`) —
+    /// ahead of the body so `peek_metadata` can read it without touching the
+    /// body at all — and finally the body itself. When `integrity` is
+    /// `Some`, a digest of `text` (the original, pre-compression string) is
+    /// appended after the body and the header's integrity tag records which
+    /// algorithm produced it.
+    fn wrap(candidate: &Candidate, text: &str, integrity: Option<HashAlgorithm>) -> Vec<u8> {
+        let digest = integrity.map(|algorithm| (algorithm, compute_digest(algorithm, text)));
+        let index_bytes = candidate.metadata_index.encode();
+
+        let trailer_len = digest.as_ref().map_or(0, |(_, bytes)| bytes.len());
+        let mut payload = Vec::with_capacity(
+            HEADER_LEN + index_bytes.len() + candidate.body.len() + trailer_len,
+        );
+        payload.push(FORMAT_VERSION);
+        payload.push(candidate.method as u8);
+        payload.extend_from_slice(&candidate.version.to_be_bytes());
+        payload.push(digest.as_ref().map_or(INTEGRITY_NONE, |(algorithm, _)| *algorithm as u8));
+        payload.extend_from_slice(&index_bytes);
+        payload.extend_from_slice(&candidate.body);
+        if let Some((_, bytes)) = digest {
+            payload.extend_from_slice(&bytes);
+        }
+        payload
+    }
+
+    /// Ingest a message into the background template miner, registering any
+    /// newly-discovered template and evicting the lowest-value auto-mined
+    /// template if the auto table has grown past its bound.
+    fn observe_for_mining(&self, text: &str) {
+        let mined = self.miner.borrow_mut().observe(text);
+        if let Some(mined) = mined {
+            self.template_library
+                .borrow_mut()
+                .register(mined.id, mined.pattern);
+            self.miner.borrow_mut().track(mined.id);
+        }
+
+        let evicted = self.miner.borrow().evict_candidate();
+        if let Some(evicted_id) = evicted {
+            self.template_library.borrow_mut().remove(evicted_id);
+            self.miner.borrow_mut().forget(evicted_id);
+        }
+    }
+
+    fn compress_binary_semantic(template_id: u32, slots: &[String]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&template_id.to_be_bytes());
         payload.push(slots.len() as u8);
 
         for slot in slots {
@@ -123,46 +428,187 @@ impl Compressor {
             payload.extend_from_slice(slot_bytes);
         }
 
-        Ok(payload)
+        payload
     }
 
-    fn compress_auralite(&self, text: &str) -> Result<Vec<u8>> {
-        // Simple AuraLite implementation
-        let mut payload = vec![0x01]; // AuraLite method
-        let text_bytes = text.as_bytes();
-        payload.extend_from_slice(&(text_bytes.len() as u32).to_be_bytes());
-        payload.extend_from_slice(text_bytes);
-        Ok(payload)
+    /// Decompress a payload, verifying its integrity trailer if it has one.
+    /// Returns `Err(AuraError::IntegrityMismatch)` when the recomputed
+    /// digest doesn't match — a payload compressed without an integrity
+    /// algorithm carries no trailer at all, so it decodes exactly as before.
+    pub fn decompress(&self, payload: &[u8]) -> Result<String> {
+        let decoded = self.decode_inner(payload)?;
+        if let Some((algorithm, false)) = decoded.integrity {
+            return Err(AuraError::IntegrityMismatch(algorithm.as_str().to_string()));
+        }
+        Ok(decoded.text)
     }
 
-    pub fn decompress(&self, payload: &[u8]) -> Result<String> {
-        if payload.is_empty() {
+    /// Like `decompress`, but also returns the `DecompressionMetadata` side-
+    /// channel (method, template IDs, and integrity algorithm/result) that
+    /// `ClientSDK::decode_payload` and `ServerSDK::extract_metadata` surface
+    /// to callers.
+    pub fn decompress_with_metadata(&self, payload: &[u8]) -> Result<(String, DecompressionMetadata)> {
+        let decoded = self.decode_inner(payload)?;
+        if let Some((algorithm, false)) = decoded.integrity {
+            return Err(AuraError::IntegrityMismatch(algorithm.as_str().to_string()));
+        }
+
+        let metadata = DecompressionMetadata {
+            method: decoded.method.as_str().to_string(),
+            template_ids: decoded.template_ids,
+            integrity_algorithm: decoded.integrity.map(|(algorithm, _)| algorithm.as_str().to_string()),
+            integrity_verified: decoded.integrity.map(|(_, verified)| verified),
+        };
+        Ok((decoded.text, metadata))
+    }
+
+    fn decode_inner(&self, payload: &[u8]) -> Result<DecodedPayload> {
+        if payload.len() < HEADER_LEN {
             return Err(AuraError::InvalidPayload("Empty payload".to_string()));
         }
 
-        let method = CompressionMethod::from_byte(payload[0])?;
+        let format_version = payload[0];
+        if format_version != FORMAT_VERSION {
+            return Err(AuraError::UnsupportedFormatVersion(format_version));
+        }
 
-        match method {
-            CompressionMethod::BinarySemantic => self.decompress_binary_semantic(&payload[1..]),
+        let method = CompressionMethod::from_byte(payload[1])?;
+        let version = u32::from_be_bytes([payload[2], payload[3], payload[4], payload[5]]);
+        let integrity_tag = payload[6];
+        let mut data = &payload[HEADER_LEN..];
+
+        let (metadata_index, index_len) = MetadataIndex::decode(data)?;
+        data = &data[index_len..];
+
+        let integrity_algorithm = if integrity_tag == INTEGRITY_NONE {
+            None
+        } else {
+            Some(HashAlgorithm::from_byte(integrity_tag)?)
+        };
+
+        let expected_digest = match integrity_algorithm {
+            Some(algorithm) => {
+                let digest_len = algorithm.digest_len();
+                if data.len() < digest_len {
+                    return Err(AuraError::InvalidPayload(
+                        "Truncated integrity trailer".to_string(),
+                    ));
+                }
+                let split_at = data.len() - digest_len;
+                let (body, trailer) = data.split_at(split_at);
+                data = body;
+                Some(trailer.to_vec())
+            }
+            None => None,
+        };
+
+        let (text, template_ids) = match method {
+            CompressionMethod::BinarySemantic => {
+                let current = self.template_library.borrow().version();
+                if version > current {
+                    // This side hasn't synced that far yet (see
+                    // `Compressor::import_template_delta`) — distinct from a
+                    // hard version mismatch, since catching up resolves it.
+                    return Err(AuraError::UnknownTemplateVersion(version));
+                }
+                if version != current {
+                    return Err(AuraError::VersionMismatch(
+                        "template library".to_string(),
+                        version,
+                        current,
+                    ));
+                }
+                let (text, template_id) = self.decompress_binary_semantic(data)?;
+                (text, vec![template_id])
+            }
             CompressionMethod::AuraLite | CompressionMethod::AuraLiteV2 => {
-                self.decompress_auralite(&payload[1..])
+                (self.decompress_auralite(data)?, vec![])
+            }
+            CompressionMethod::AuraFsst => {
+                let current = self.fsst_table.as_ref().map(|t| t.version()).unwrap_or(0);
+                if version != current {
+                    return Err(AuraError::VersionMismatch(
+                        "AuraFsst symbol table".to_string(),
+                        version,
+                        current,
+                    ));
+                }
+                (self.decompress_aura_fsst(data)?, vec![])
+            }
+            CompressionMethod::Brio => {
+                let text = brio::decompress(data)
+                    .map_err(AuraError::DecompressionFailed)
+                    .and_then(|bytes| {
+                        String::from_utf8(bytes).map_err(|e| AuraError::DecompressionFailed(e.to_string()))
+                    })?;
+                (text, vec![])
             }
             CompressionMethod::Uncompressed => {
-                String::from_utf8(payload[1..].to_vec())
-                    .map_err(|e| AuraError::DecompressionFailed(e.to_string()))
+                let text = String::from_utf8(data.to_vec())
+                    .map_err(|e| AuraError::DecompressionFailed(e.to_string()))?;
+                (text, vec![])
             }
-            _ => Err(AuraError::UnknownMethod(payload[0])),
+        };
+
+        let integrity = match (integrity_algorithm, expected_digest) {
+            (Some(algorithm), Some(expected)) => {
+                let actual = compute_digest(algorithm, &text);
+                Some((algorithm, actual == expected))
+            }
+            _ => None,
+        };
+
+        // The index is the authoritative source once present; `template_ids`
+        // above only covers the case of a payload with no index at all
+        // (legacy / non-semantic methods).
+        let index_template_ids = metadata_index.template_ids();
+        let template_ids = if index_template_ids.is_empty() { template_ids } else { index_template_ids };
+
+        Ok(DecodedPayload { text, method, template_ids, integrity })
+    }
+
+    /// Read a payload's `DecompressionMetadata` from its header and metadata
+    /// index alone, without decompressing or integrity-checking the body —
+    /// the "371x speedup" fast path: a server can route or audit on which
+    /// templates a payload touched at a fraction of the cost of a full
+    /// `decompress_with_metadata`. `integrity_verified` is always `None`
+    /// here since verifying the digest requires the decompressed text.
+    pub fn peek_metadata(&self, payload: &[u8]) -> Result<DecompressionMetadata> {
+        if payload.len() < HEADER_LEN {
+            return Err(AuraError::InvalidPayload("Empty payload".to_string()));
         }
+
+        let format_version = payload[0];
+        if format_version != FORMAT_VERSION {
+            return Err(AuraError::UnsupportedFormatVersion(format_version));
+        }
+
+        let method = CompressionMethod::from_byte(payload[1])?;
+        let integrity_tag = payload[6];
+        let integrity_algorithm = if integrity_tag == INTEGRITY_NONE {
+            None
+        } else {
+            Some(HashAlgorithm::from_byte(integrity_tag)?)
+        };
+
+        let (metadata_index, _) = MetadataIndex::decode(&payload[HEADER_LEN..])?;
+
+        Ok(DecompressionMetadata {
+            method: method.as_str().to_string(),
+            template_ids: metadata_index.template_ids(),
+            integrity_algorithm: integrity_algorithm.map(|algorithm| algorithm.as_str().to_string()),
+            integrity_verified: None,
+        })
     }
 
-    fn decompress_binary_semantic(&self, data: &[u8]) -> Result<String> {
-        if data.len() < 2 {
+    fn decompress_binary_semantic(&self, data: &[u8]) -> Result<(String, u32)> {
+        if data.len() < 5 {
             return Err(AuraError::InvalidPayload("Malformed binary payload".to_string()));
         }
 
-        let template_id = data[0] as u32;
-        let slot_count = data[1] as usize;
-        let mut offset = 2;
+        let template_id = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        let slot_count = data[4] as usize;
+        let mut offset = 5;
         let mut slots = Vec::new();
 
         for _ in 0..slot_count {
@@ -183,7 +629,8 @@ impl Compressor {
             offset += slot_len;
         }
 
-        self.template_library.format_template(template_id, &slots)
+        let text = self.template_library.borrow().format_template(template_id, &slots)?;
+        Ok((text, template_id))
     }
 
     fn decompress_auralite(&self, data: &[u8]) -> Result<String> {
@@ -201,11 +648,222 @@ impl Compressor {
             .map_err(|e| AuraError::DecompressionFailed(e.to_string()))
     }
 
-    pub fn register_template(&mut self, template_id: u32, pattern: String) {
-        self.template_library.register(template_id, pattern);
+    fn decompress_aura_fsst(&self, data: &[u8]) -> Result<String> {
+        let table = self.fsst_table.as_ref().ok_or_else(|| {
+            AuraError::DecompressionFailed("No AuraFsst symbol table loaded".to_string())
+        })?;
+
+        table.decompress(data).map_err(AuraError::DecompressionFailed)
+    }
+
+    pub fn register_template(&self, template_id: u32, pattern: String) {
+        self.template_library.borrow_mut().register(template_id, pattern);
+    }
+
+    /// Every registered template's pattern and declared slot names/defaults,
+    /// for a caller to introspect what a template expects before filling it
+    /// with `compress_named`.
+    pub fn list_templates(&self) -> HashMap<u32, TemplateInfo> {
+        self.template_library.borrow().list()
+    }
+
+    /// Like `compress`, but fills `template_id`'s slots by name instead of
+    /// position: each declared slot takes its entry in `named_slots` or,
+    /// absent that, its declared default (see `TemplateLibrary::slot_defs`).
+    /// The text to compress is the template filled with the resolved slots,
+    /// so there's no separate `text` argument to keep in sync with them.
+    pub fn compress_named(
+        &self,
+        template_id: u32,
+        named_slots: HashMap<String, String>,
+        integrity: Option<HashAlgorithm>,
+    ) -> Result<(Vec<u8>, CompressionMethod, CompressionMetadata)> {
+        let slots = self
+            .template_library
+            .borrow()
+            .resolve_named_slots(template_id, &named_slots)?;
+        let text = self.template_library.borrow().format_template(template_id, &slots)?;
+        self.compress(&text, Some(template_id), Some(slots), integrity)
+    }
+
+    /// The template registry version this compressor currently embeds in
+    /// binary-semantic payload headers.
+    pub fn template_registry_version(&self) -> u32 {
+        self.template_library.borrow().version()
+    }
+
+    /// Every registry change since `since_version`, for a peer to apply with
+    /// `import_template_delta` and catch its own registry up incrementally.
+    pub fn export_template_delta(&self, since_version: u32) -> Vec<crate::template_library::RegistryChange> {
+        self.template_library.borrow().export_since(since_version)
+    }
+
+    /// Apply a delta received from a peer's `export_template_delta`.
+    pub fn import_template_delta(&self, changes: &[crate::template_library::RegistryChange]) {
+        self.template_library.borrow_mut().import_delta(changes);
+    }
+
+    /// The label of the highest-severity rule matching `text`, or
+    /// `"general"` when nothing matches (including when no rule database
+    /// was loaded).
+    pub fn classify_intent(&self, text: &str) -> String {
+        self.rule_engine.classify(text)
+    }
+
+    /// `false` when any blocking rule matches `text`. Logs the match via
+    /// `log::warn!`, including this compressor's session/user IDs, whenever
+    /// `enable_audit_logging` is set.
+    pub fn screen_text(&self, text: &str) -> bool {
+        let blocking = self.rule_engine.blocking_matches(text);
+        if blocking.is_empty() {
+            return true;
+        }
+
+        if self.enable_audit_logging {
+            log::warn!(
+                "Blocked payload for session={:?} user={:?}: rules={:?}",
+                self.session_id,
+                self.user_id,
+                blocking.iter().map(|rule| rule.label.as_str()).collect::<Vec<_>>(),
+            );
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compressor() -> Compressor {
+        Compressor::new(true, None, false, None, None, None)
+    }
+
+    #[test]
+    fn never_worse_than_the_uncompressed_candidate() {
+        let compressor = compressor();
+        // High-entropy, no registered template matches it, so Brio/AuraFsst
+        // gain nothing from it either - the arbitration still has to land on
+        // something no larger than the Uncompressed payload: the header, the
+        // one-byte empty metadata index, and the raw text.
+        let text = "qz7 vK2p mN9x LwR4 tJ8y HdG1 sF6a ZxC3";
+        let (payload, _method, metadata) = compressor.compress(text, None, None, None).unwrap();
+
+        assert!(payload.len() <= HEADER_LEN + 1 + text.len());
+        assert_eq!(metadata.original_size, text.len());
+        assert_eq!(compressor.decompress(&payload).unwrap(), text);
+    }
+
+    #[test]
+    fn binary_semantic_candidate_carries_the_template_library_version() {
+        let compressor = compressor();
+        compressor.register_template(900, "Known for {0}.".to_string());
+        let version = compressor.template_registry_version();
+        assert_ne!(version, 0, "register_template should have bumped the version");
+
+        let candidate = compressor
+            .semantic_candidate("Known for testing.", Some(900), Some(vec!["testing".to_string()]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(candidate.version, version);
+
+        let payload = Compressor::wrap(&candidate, "Known for testing.", None);
+        let encoded_version = u32::from_be_bytes([payload[2], payload[3], payload[4], payload[5]]);
+        assert_eq!(encoded_version, version);
+    }
+
+    #[test]
+    fn selects_the_smallest_real_candidate_not_a_fixed_try_order() {
+        let compressor = compressor();
+        // No template matches this, so it's a straight generic-vs-generic
+        // comparison - whichever codec actually encodes it smaller should
+        // win, not whichever one used to be tried first.
+        let text = "ab".repeat(200);
+        let brio = compressor.brio_candidate(&text);
+        let uncompressed = compressor.uncompressed_candidate(&text);
+        let expected = if brio.total_len() <= uncompressed.total_len() { brio } else { uncompressed };
+
+        let (payload, method, _metadata) = compressor.compress(&text, None, None, None).unwrap();
+        assert_eq!(method, expected.method);
+        assert_eq!(payload.len(), expected.total_len());
+    }
+
+    #[test]
+    fn integrity_trailer_round_trips_and_catches_tampering() {
+        let compressor = compressor();
+        let text = "The capital of France is Paris.";
+        let (mut payload, _method, _metadata) = compressor
+            .compress(text, None, None, Some(HashAlgorithm::Sha256))
+            .unwrap();
+
+        assert_eq!(compressor.decompress(&payload).unwrap(), text);
+
+        // Flip the low bit of a body byte (well clear of the header) so the
+        // body still decodes as valid UTF-8, and the recomputed digest should
+        // no longer match.
+        let tamper_at = payload.len() - HashAlgorithm::Sha256.digest_len() - 1;
+        payload[tamper_at] ^= 0x01;
+        let err = compressor.decompress(&payload).unwrap_err();
+        assert!(matches!(err, AuraError::IntegrityMismatch(algorithm) if algorithm == "sha256"));
+    }
+
+    #[test]
+    fn payload_without_integrity_has_no_trailer_to_check() {
+        let compressor = compressor();
+        let text = "I cannot browse the internet.";
+        let (payload, _method, _metadata) = compressor.compress(text, None, None, None).unwrap();
+
+        let (decoded, metadata) = compressor.decompress_with_metadata(&payload).unwrap();
+        assert_eq!(decoded, text);
+        assert_eq!(metadata.integrity_algorithm, None);
+        assert_eq!(metadata.integrity_verified, None);
+    }
+
+    #[test]
+    fn peek_metadata_matches_decompress_with_metadata_without_reading_the_body() {
+        let compressor = compressor();
+        let (payload, _method, _metadata) = compressor
+            .compress("The capital of France is Paris.", None, None, Some(HashAlgorithm::Md5))
+            .unwrap();
+
+        let peeked = compressor.peek_metadata(&payload).unwrap();
+        let (_, full) = compressor.decompress_with_metadata(&payload).unwrap();
+
+        assert_eq!(peeked.method, full.method);
+        assert_eq!(peeked.template_ids, full.template_ids);
+        assert_eq!(peeked.integrity_algorithm, full.integrity_algorithm);
+        // peek_metadata never touches the body, so it can't verify the
+        // digest - only decompress_with_metadata can.
+        assert_eq!(peeked.integrity_verified, None);
+        assert_eq!(full.integrity_verified, Some(true));
+    }
+
+    #[test]
+    fn rejects_an_unsupported_format_version() {
+        let compressor = compressor();
+        let (mut payload, _method, _metadata) = compressor.compress("hello", None, None, None).unwrap();
+        payload[0] = FORMAT_VERSION + 1;
+
+        let err = compressor.decompress(&payload).unwrap_err();
+        assert!(matches!(err, AuraError::UnsupportedFormatVersion(v) if v == FORMAT_VERSION + 1));
     }
 
-    pub fn list_templates(&self) -> HashMap<u32, String> {
-        self.template_library.list()
+    #[test]
+    fn rejects_a_binary_semantic_payload_from_a_stale_template_version() {
+        let compressor = compressor();
+        // Long literal, short slot: the binary-semantic encoding (43 bytes)
+        // beats Uncompressed (47 bytes), so arbitration picks it.
+        let (payload, method, _metadata) = compressor
+            .compress("I don't have the ability to see images.", None, None, None)
+            .unwrap();
+        assert_eq!(method, CompressionMethod::BinarySemantic);
+
+        // Registering a new template bumps the library version past what
+        // this payload was encoded against.
+        compressor.register_template(999, "Placeholder {0}.".to_string());
+
+        let err = compressor.decompress(&payload).unwrap_err();
+        assert!(matches!(err, AuraError::VersionMismatch(what, 0, 1) if what == "template library"));
     }
 }
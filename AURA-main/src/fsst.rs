@@ -0,0 +1,240 @@
+//! FSST-style trained symbol table compression
+//!
+//! A lightweight adaptation of the FSST (Fast Static Symbol Table) scheme used
+//! by analytical databases: a small table of byte-string symbols (length 1-8)
+//! is trained once over a corpus of representative samples, then used to pack
+//! runs of bytes into single-byte codes. Code 255 is reserved as an escape for
+//! bytes that have no matching symbol, so any input is always representable.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Maximum number of trained symbols (codes 0-254; 255 is the escape byte).
+pub const MAX_SYMBOLS: usize = 255;
+/// Escape code: followed by one literal byte that has no symbol of its own.
+pub const ESCAPE_CODE: u8 = 255;
+const MAX_SYMBOL_LEN: usize = 8;
+const TRAINING_ROUNDS: usize = 5;
+
+/// A trained FSST-style symbol table.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolTable {
+    /// Symbols indexed by code; kept longest-first so greedy matching prefers
+    /// the longest symbol at each position.
+    symbols: Vec<Vec<u8>>,
+    /// Bumped every time the table is (re)trained, and embedded in AuraFsst
+    /// payload headers so a decoder can reject a payload encoded against a
+    /// different table instead of expanding codes against the wrong symbols.
+    #[serde(default)]
+    version: u32,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self {
+            symbols: Vec::new(),
+            version: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Train a symbol table over a corpus of sample strings.
+    ///
+    /// Each round greedily re-encodes every sample with the current table
+    /// (always preferring the longest matching symbol), tallies single-symbol
+    /// and adjacent-pair frequencies, then forms the next round's candidates
+    /// from the current symbols, frequent pair concatenations, and every raw
+    /// byte seen in the corpus. Candidates are scored by the approximate byte
+    /// gain `frequency * (len - 1)` and the top `MAX_SYMBOLS` are kept.
+    pub fn train(samples: &[String]) -> Self {
+        if samples.is_empty() {
+            return Self::new();
+        }
+
+        let all_bytes = Self::all_bytes(samples);
+        let mut table = Self {
+            symbols: all_bytes.iter().take(MAX_SYMBOLS).cloned().collect(),
+            version: 1,
+        };
+
+        for _ in 0..TRAINING_ROUNDS {
+            let mut symbol_freq: HashMap<Vec<u8>, usize> = HashMap::new();
+            let mut pair_freq: HashMap<(Vec<u8>, Vec<u8>), usize> = HashMap::new();
+
+            for sample in samples {
+                let encoded = table.greedy_encode_symbols(sample.as_bytes());
+                for sym in &encoded {
+                    *symbol_freq.entry(sym.clone()).or_insert(0) += 1;
+                }
+                for pair in encoded.windows(2) {
+                    *pair_freq
+                        .entry((pair[0].clone(), pair[1].clone()))
+                        .or_insert(0) += 1;
+                }
+            }
+
+            let mut candidates: HashMap<Vec<u8>, usize> = HashMap::new();
+            for sym in &table.symbols {
+                let freq = *symbol_freq.get(sym).unwrap_or(&0);
+                *candidates.entry(sym.clone()).or_insert(0) += freq;
+            }
+            for ((a, b), freq) in &pair_freq {
+                let mut merged = a.clone();
+                merged.extend_from_slice(b);
+                merged.truncate(MAX_SYMBOL_LEN);
+                *candidates.entry(merged).or_insert(0) += freq;
+            }
+            for byte in &all_bytes {
+                candidates.entry(byte.clone()).or_insert(0);
+            }
+
+            let mut scored: Vec<(Vec<u8>, usize)> = candidates
+                .into_iter()
+                .map(|(sym, freq)| {
+                    let gain = freq * sym.len().saturating_sub(1);
+                    (sym, gain)
+                })
+                .collect();
+            // Break ties deterministically so training is reproducible.
+            scored.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            scored.truncate(MAX_SYMBOLS);
+
+            table.symbols = scored.into_iter().map(|(sym, _)| sym).collect();
+            table.symbols.sort_by(|a, b| b.len().cmp(&a.len()));
+        }
+
+        table
+    }
+
+    fn all_bytes(samples: &[String]) -> Vec<Vec<u8>> {
+        let mut seen = [false; 256];
+        for sample in samples {
+            for &b in sample.as_bytes() {
+                seen[b as usize] = true;
+            }
+        }
+        (0..256u32)
+            .filter(|&b| seen[b as usize])
+            .map(|b| vec![b as u8])
+            .collect()
+    }
+
+    fn longest_match<'a>(&self, input: &'a [u8]) -> Option<&'a [u8]> {
+        let max_len = input.len().min(MAX_SYMBOL_LEN);
+        (1..=max_len)
+            .rev()
+            .map(|len| &input[..len])
+            .find(|candidate| self.symbols.iter().any(|s| s.as_slice() == *candidate))
+    }
+
+    /// Encode `input` as a sequence of symbol byte-strings (training only).
+    fn greedy_encode_symbols(&self, input: &[u8]) -> Vec<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut pos = 0;
+        while pos < input.len() {
+            match self.longest_match(&input[pos..]) {
+                Some(sym) => {
+                    pos += sym.len();
+                    out.push(sym.to_vec());
+                }
+                None => {
+                    out.push(vec![input[pos]]);
+                    pos += 1;
+                }
+            }
+        }
+        out
+    }
+
+    /// Compress `text` into a stream of 1-byte codes, escaping bytes that
+    /// have no matching symbol.
+    pub fn compress(&self, text: &str) -> Vec<u8> {
+        let input = text.as_bytes();
+        let mut out = Vec::with_capacity(input.len());
+        let mut pos = 0;
+        while pos < input.len() {
+            match self.longest_match(&input[pos..]) {
+                Some(sym) => {
+                    let code = self
+                        .symbols
+                        .iter()
+                        .position(|s| s.as_slice() == sym)
+                        .expect("matched symbol must be in table") as u8;
+                    out.push(code);
+                    pos += sym.len();
+                }
+                None => {
+                    out.push(ESCAPE_CODE);
+                    out.push(input[pos]);
+                    pos += 1;
+                }
+            }
+        }
+        out
+    }
+
+    /// Expand a code stream produced by `compress`.
+    pub fn decompress(&self, data: &[u8]) -> Result<String, String> {
+        let mut out = Vec::with_capacity(data.len() * 2);
+        let mut pos = 0;
+        while pos < data.len() {
+            let code = data[pos];
+            pos += 1;
+            if code == ESCAPE_CODE {
+                let byte = *data
+                    .get(pos)
+                    .ok_or_else(|| "truncated escape sequence".to_string())?;
+                out.push(byte);
+                pos += 1;
+            } else {
+                let sym = self
+                    .symbols
+                    .get(code as usize)
+                    .ok_or_else(|| format!("unknown symbol code {}", code))?;
+                out.extend_from_slice(sym);
+            }
+        }
+        String::from_utf8(out).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trains_and_round_trips() {
+        let samples: Vec<String> = vec![
+            "I cannot browse the internet.".to_string(),
+            "I cannot access real-time data.".to_string(),
+            "I cannot browse the web right now.".to_string(),
+        ];
+        let table = SymbolTable::train(&samples);
+        assert!(!table.is_empty());
+
+        for sample in &samples {
+            let encoded = table.compress(sample);
+            let decoded = table.decompress(&encoded).unwrap();
+            assert_eq!(&decoded, sample);
+        }
+    }
+
+    #[test]
+    fn empty_table_falls_back_to_escapes() {
+        let table = SymbolTable::new();
+        let encoded = table.compress("hi");
+        assert_eq!(encoded, vec![ESCAPE_CODE, b'h', ESCAPE_CODE, b'i']);
+        assert_eq!(table.decompress(&encoded).unwrap(), "hi");
+    }
+}
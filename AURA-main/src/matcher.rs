@@ -0,0 +1,278 @@
+//! Aho-Corasick backed multi-template matcher
+//!
+//! Each registered template is decomposed into an ordered sequence of literal
+//! anchors and placeholder gaps. A single Aho-Corasick automaton is built over
+//! every literal anchor from every template, so one pass over the input text
+//! locates all candidate templates instead of scanning the template table
+//! linearly and re-parsing each pattern per candidate.
+
+use aho_corasick::AhoCorasick;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder,
+}
+
+fn parse_segments(pattern: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '{' {
+            while let Some(next) = chars.next() {
+                if next == '}' {
+                    break;
+                }
+            }
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            }
+            segments.push(Segment::Placeholder);
+        } else {
+            literal.push(c);
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    segments
+}
+
+#[derive(Debug, Clone)]
+struct CompiledTemplate {
+    id: u32,
+    segments: Vec<Segment>,
+    slot_count: usize,
+}
+
+/// Resolves the best-matching template (and its slot values) for arbitrary
+/// input text in a single automaton pass.
+pub struct TemplateMatcher {
+    templates: Vec<CompiledTemplate>,
+    automaton: Option<AhoCorasick>,
+    /// Automaton pattern index -> every (template index, segment index) that
+    /// shares that literal text. Patterns are deduplicated by text before
+    /// being handed to the automaton, so two templates whose literal anchors
+    /// are spelled identically (e.g. a shared trailing ".") resolve to the
+    /// same pattern id; each occurrence is then credited to every owner.
+    pattern_owners: Vec<Vec<(usize, usize)>>,
+}
+
+impl TemplateMatcher {
+    /// Build (or rebuild) the matcher from the current template table.
+    pub fn build(templates: &HashMap<u32, String>) -> Self {
+        let mut ids: Vec<&u32> = templates.keys().collect();
+        ids.sort();
+
+        let mut compiled = Vec::with_capacity(ids.len());
+        let mut literals: Vec<String> = Vec::new();
+        let mut pattern_ids: HashMap<String, usize> = HashMap::new();
+        let mut pattern_owners: Vec<Vec<(usize, usize)>> = Vec::new();
+
+        for &id in &ids {
+            let pattern = &templates[id];
+            let segments = parse_segments(pattern);
+            let slot_count = segments
+                .iter()
+                .filter(|s| matches!(s, Segment::Placeholder))
+                .count();
+            let template_index = compiled.len();
+
+            for (seg_index, seg) in segments.iter().enumerate() {
+                if let Segment::Literal(lit) = seg {
+                    let pattern_id = *pattern_ids.entry(lit.clone()).or_insert_with(|| {
+                        literals.push(lit.clone());
+                        pattern_owners.push(Vec::new());
+                        literals.len() - 1
+                    });
+                    pattern_owners[pattern_id].push((template_index, seg_index));
+                }
+            }
+
+            compiled.push(CompiledTemplate {
+                id: *id,
+                segments,
+                slot_count,
+            });
+        }
+
+        let automaton = if literals.is_empty() {
+            None
+        } else {
+            AhoCorasick::new(&literals).ok()
+        };
+
+        Self {
+            templates: compiled,
+            automaton,
+            pattern_owners,
+        }
+    }
+
+    /// Find the best matching template for `text`, returning its id and the
+    /// extracted slot values in placeholder order.
+    pub fn match_text(&self, text: &str) -> Option<(u32, Vec<String>)> {
+        let automaton = self.automaton.as_ref()?;
+
+        // Group literal occurrences by the (template, segment) they belong to.
+        // Overlapping search, so a short literal (e.g. "The ") can't hide a
+        // longer one sharing its start (e.g. "The capital of ").
+        let mut occurrences: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for mat in automaton.find_overlapping_iter(text) {
+            for &owner in &self.pattern_owners[mat.pattern().as_usize()] {
+                occurrences
+                    .entry(owner)
+                    .or_default()
+                    .push((mat.start(), mat.end()));
+            }
+        }
+        for positions in occurrences.values_mut() {
+            positions.sort_unstable();
+        }
+
+        let mut best: Option<(u32, Vec<String>, usize, usize)> = None;
+
+        for (template_index, template) in self.templates.iter().enumerate() {
+            if let Some(slots) = self.try_match(text, template_index, template, &occurrences) {
+                let coverage: usize = template
+                    .segments
+                    .iter()
+                    .filter_map(|s| match s {
+                        Segment::Literal(l) => Some(l.len()),
+                        Segment::Placeholder => None,
+                    })
+                    .sum();
+
+                let is_better = match &best {
+                    None => true,
+                    Some((_, _, best_coverage, best_slots)) => {
+                        coverage > *best_coverage
+                            || (coverage == *best_coverage && template.slot_count < *best_slots)
+                    }
+                };
+
+                if is_better {
+                    best = Some((template.id, slots, coverage, template.slot_count));
+                }
+            }
+        }
+
+        best.map(|(id, slots, _, _)| (id, slots))
+    }
+
+    /// Attempt to satisfy `template` against `text` using the literal
+    /// occurrences found by the automaton pass.
+    fn try_match(
+        &self,
+        text: &str,
+        template_index: usize,
+        template: &CompiledTemplate,
+        occurrences: &HashMap<(usize, usize), Vec<(usize, usize)>>,
+    ) -> Option<Vec<String>> {
+        let has_literal = template
+            .segments
+            .iter()
+            .any(|s| matches!(s, Segment::Literal(_)));
+        if !has_literal {
+            // An all-placeholder template can't be anchored safely; refuse it
+            // rather than guessing.
+            return None;
+        }
+
+        // Resolve literal positions left-to-right: each literal must occur at
+        // or after the end of the previous one, in order and without overlap.
+        let mut literal_positions: Vec<(usize, usize)> = Vec::new();
+        let mut cursor = 0usize;
+        for (seg_index, seg) in template.segments.iter().enumerate() {
+            if matches!(seg, Segment::Literal(_)) {
+                let candidates = occurrences.get(&(template_index, seg_index))?;
+                let &(start, end) = candidates.iter().find(|&&(start, _)| start >= cursor)?;
+                literal_positions.push((start, end));
+                cursor = end;
+            }
+        }
+
+        let starts_with_literal = matches!(template.segments.first(), Some(Segment::Literal(_)));
+        let ends_with_literal = matches!(template.segments.last(), Some(Segment::Literal(_)));
+
+        if starts_with_literal && literal_positions[0].0 != 0 {
+            return None;
+        }
+        if ends_with_literal && literal_positions.last().unwrap().1 != text.len() {
+            return None;
+        }
+
+        // Walk the segments again, filling in slot text between literals.
+        let mut slots = Vec::new();
+        let mut literal_iter = literal_positions.iter().peekable();
+        let mut prev_end = 0usize;
+
+        for seg in &template.segments {
+            match seg {
+                Segment::Literal(_) => {
+                    let &(_, end) = literal_iter.next().unwrap();
+                    prev_end = end;
+                }
+                Segment::Placeholder => {
+                    let next_start = match literal_iter.peek() {
+                        Some(&&(start, _)) => start,
+                        None => text.len(),
+                    };
+                    if next_start < prev_end {
+                        return None;
+                    }
+                    let slot_text = &text[prev_end..next_start];
+                    if slot_text.is_empty() {
+                        return None;
+                    }
+                    slots.push(slot_text.to_string());
+                }
+            }
+        }
+
+        Some(slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> HashMap<u32, String> {
+        let mut templates = HashMap::new();
+        templates.insert(1, "I cannot {0}.".to_string());
+        templates.insert(11, "{0} is {1}.".to_string());
+        templates.insert(12, "The capital of {0} is {1}.".to_string());
+        templates
+    }
+
+    #[test]
+    fn matches_exact_leading_and_trailing_literals() {
+        let matcher = TemplateMatcher::build(&library());
+        let (id, slots) = matcher.match_text("I cannot browse the internet.").unwrap();
+        assert_eq!(id, 1);
+        assert_eq!(slots, vec!["browse the internet".to_string()]);
+    }
+
+    #[test]
+    fn prefers_more_specific_template_over_generic() {
+        let matcher = TemplateMatcher::build(&library());
+        let (id, slots) = matcher
+            .match_text("The capital of France is Paris.")
+            .unwrap();
+        assert_eq!(id, 12);
+        assert_eq!(slots, vec!["France".to_string(), "Paris".to_string()]);
+    }
+
+    #[test]
+    fn rejects_empty_slot_captures() {
+        let mut templates = HashMap::new();
+        templates.insert(1, "I cannot {0}.".to_string());
+        let matcher = TemplateMatcher::build(&templates);
+        assert!(matcher.match_text("I cannot .").is_none());
+    }
+}
@@ -0,0 +1,155 @@
+//! Lazily-decodable metadata index for compressed payloads
+//!
+//! `Compressor::wrap` appends this index right after the payload header
+//! (before the body), so a server can recover which templates a payload
+//! touched, and where each substitution landed in the decompressed text, by
+//! parsing a small fixed header and a table of entries — without paying the
+//! cost of decompressing the body at all. See `Compressor::peek_metadata`.
+
+use crate::{AuraError, Result};
+
+/// Index format version. Only version 1 is currently produced; a reader
+/// must reject anything else instead of guessing at an incompatible shape.
+pub const INDEX_VERSION: u8 = 1;
+
+/// Leading byte meaning "no index follows" — every candidate with no
+/// template references (AuraLite, Brio, AuraFsst, Uncompressed) encodes to
+/// just this one byte, so the common case costs nothing beyond it.
+pub const NO_INDEX: u8 = 0;
+
+/// `index_version` + `flags` (reserved) + `template_ref_count` + `original_len` (u32 BE).
+const FIXED_HEADER_LEN: usize = 1 + 1 + 1 + 4;
+/// Per entry: `template_id` + `offset` + `length` (u32 BE each), all
+/// positions into the *decompressed* text.
+const ENTRY_LEN: usize = 4 + 4 + 4;
+
+/// One template substitution: which template produced it, and where its
+/// filled-in slot landed in the decompressed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateRef {
+    pub template_id: u32,
+    pub offset: u32,
+    pub length: u32,
+}
+
+/// The index itself. `template_refs` empty means the candidate carries no
+/// template references, which `encode` collapses to the single `NO_INDEX`
+/// byte rather than writing a zero-entry table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataIndex {
+    pub original_len: u32,
+    pub template_refs: Vec<TemplateRef>,
+}
+
+impl MetadataIndex {
+    pub fn encode(&self) -> Vec<u8> {
+        if self.template_refs.is_empty() {
+            return vec![NO_INDEX];
+        }
+
+        let mut bytes = Vec::with_capacity(FIXED_HEADER_LEN + self.template_refs.len() * ENTRY_LEN);
+        bytes.push(INDEX_VERSION);
+        bytes.push(0); // flags: reserved for future use
+        bytes.push(self.template_refs.len() as u8);
+        bytes.extend_from_slice(&self.original_len.to_be_bytes());
+
+        for reference in &self.template_refs {
+            bytes.extend_from_slice(&reference.template_id.to_be_bytes());
+            bytes.extend_from_slice(&reference.offset.to_be_bytes());
+            bytes.extend_from_slice(&reference.length.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    /// Parse the index from the start of `data`, returning it alongside how
+    /// many bytes it occupied so the caller can locate whatever follows
+    /// (the body). Never reads past the table — a `NO_INDEX` tag consumes
+    /// exactly one byte.
+    pub fn decode(data: &[u8]) -> Result<(Self, usize)> {
+        let tag = *data.first().ok_or_else(|| {
+            AuraError::InvalidPayload("Missing metadata index tag".to_string())
+        })?;
+
+        if tag == NO_INDEX {
+            return Ok((Self::default(), 1));
+        }
+        if tag != INDEX_VERSION {
+            return Err(AuraError::InvalidPayload(format!(
+                "Unsupported metadata index version: {}",
+                tag
+            )));
+        }
+        if data.len() < FIXED_HEADER_LEN {
+            return Err(AuraError::InvalidPayload("Truncated metadata index header".to_string()));
+        }
+
+        let count = data[2] as usize;
+        let original_len = u32::from_be_bytes([data[3], data[4], data[5], data[6]]);
+        let mut offset = FIXED_HEADER_LEN;
+        let mut template_refs = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            if offset + ENTRY_LEN > data.len() {
+                return Err(AuraError::InvalidPayload("Truncated metadata index table".to_string()));
+            }
+
+            let template_id = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap());
+            let entry_offset = u32::from_be_bytes(data[offset + 4..offset + 8].try_into().unwrap());
+            let length = u32::from_be_bytes(data[offset + 8..offset + 12].try_into().unwrap());
+            template_refs.push(TemplateRef { template_id, offset: entry_offset, length });
+            offset += ENTRY_LEN;
+        }
+
+        Ok((Self { original_len, template_refs }, offset))
+    }
+
+    /// The distinct template IDs referenced, in first-seen order.
+    pub fn template_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = Vec::new();
+        for reference in &self.template_refs {
+            if !ids.contains(&reference.template_id) {
+                ids.push(reference.template_id);
+            }
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_index_round_trip() {
+        let index = MetadataIndex::default();
+        let bytes = index.encode();
+        assert_eq!(bytes, vec![NO_INDEX]);
+
+        let (decoded, consumed) = MetadataIndex::decode(&bytes).unwrap();
+        assert_eq!(consumed, 1);
+        assert_eq!(decoded, index);
+    }
+
+    #[test]
+    fn test_template_refs_round_trip() {
+        let index = MetadataIndex {
+            original_len: 42,
+            template_refs: vec![
+                TemplateRef { template_id: 7, offset: 0, length: 5 },
+                TemplateRef { template_id: 7, offset: 10, length: 3 },
+            ],
+        };
+        let bytes = index.encode();
+
+        let (decoded, consumed) = MetadataIndex::decode(&bytes).unwrap();
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(decoded, index);
+        assert_eq!(decoded.template_ids(), vec![7]);
+    }
+
+    #[test]
+    fn test_decode_rejects_unsupported_version() {
+        assert!(MetadataIndex::decode(&[0x7F]).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! Self-mining template discovery
+//!
+//! Watches the stream of messages passed through `Compressor::compress` and
+//! proposes new templates once a recurring shape is seen often enough to pay
+//! for its own registration overhead: messages are clustered by their token
+//! count and first/last token, contiguous spans where a cluster's samples
+//! disagree are abstracted into `{0}`, `{1}`, ... placeholders, and the
+//! generalized pattern is registered under a fresh auto-assigned id once it
+//! both recurs often enough and is projected to save more bytes than it
+//! costs to store.
+
+use std::collections::{HashMap, HashSet};
+
+/// Auto-mined templates are registered starting at this id so they never
+/// collide with hand-registered or core templates.
+pub const AUTO_ID_START: u32 = 10_000;
+const MIN_CLUSTER_SIZE: usize = 3;
+const MAX_AUTO_TEMPLATES: usize = 200;
+
+#[derive(Debug, Clone, Default)]
+struct Cluster {
+    samples: Vec<Vec<String>>,
+}
+
+/// A template discovered from the conversation corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinedTemplate {
+    pub id: u32,
+    pub pattern: String,
+}
+
+/// Background miner that clusters observed messages and proposes new
+/// templates once they recur often enough.
+pub struct TemplateMiner {
+    clusters: HashMap<(usize, String, String), Cluster>,
+    next_auto_id: u32,
+    hit_counts: HashMap<u32, usize>,
+    registered_patterns: HashSet<String>,
+}
+
+impl TemplateMiner {
+    pub fn new() -> Self {
+        Self {
+            clusters: HashMap::new(),
+            next_auto_id: AUTO_ID_START,
+            hit_counts: HashMap::new(),
+            registered_patterns: HashSet::new(),
+        }
+    }
+
+    /// Record that an auto-mined template was used for a match, so low-value
+    /// templates can be identified for eviction later.
+    pub fn record_hit(&mut self, id: u32) {
+        if id >= AUTO_ID_START {
+            *self.hit_counts.entry(id).or_insert(0) += 1;
+        }
+    }
+
+    /// Track a newly registered auto template so its hit count starts at zero
+    /// instead of being absent from `evict_candidate`'s consideration.
+    pub fn track(&mut self, id: u32) {
+        self.hit_counts.entry(id).or_insert(0);
+    }
+
+    /// The lowest-hit-count auto template to evict, if the auto table has
+    /// grown past its bound.
+    pub fn evict_candidate(&self) -> Option<u32> {
+        if self.hit_counts.len() <= MAX_AUTO_TEMPLATES {
+            return None;
+        }
+        self.hit_counts
+            .iter()
+            .min_by_key(|(_, count)| **count)
+            .map(|(id, _)| *id)
+    }
+
+    pub fn forget(&mut self, id: u32) {
+        self.hit_counts.remove(&id);
+    }
+
+    /// Ingest a message, returning a newly-discovered template once its
+    /// cluster reaches `MIN_CLUSTER_SIZE` samples and the estimated net bytes
+    /// saved is positive.
+    pub fn observe(&mut self, text: &str) -> Option<MinedTemplate> {
+        let tokens: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+        if tokens.len() < 2 {
+            return None;
+        }
+
+        let key = (
+            tokens.len(),
+            tokens.first().cloned().unwrap_or_default(),
+            tokens.last().cloned().unwrap_or_default(),
+        );
+
+        let cluster = self.clusters.entry(key).or_default();
+        cluster.samples.push(tokens);
+        if cluster.samples.len() < MIN_CLUSTER_SIZE {
+            return None;
+        }
+
+        let (pattern, slot_count) = Self::generalize(&cluster.samples)?;
+        if slot_count == 0 || self.registered_patterns.contains(&pattern) {
+            return None;
+        }
+        if !Self::is_worth_registering(&pattern, &cluster.samples) {
+            return None;
+        }
+
+        self.registered_patterns.insert(pattern.clone());
+        let id = self.next_auto_id;
+        self.next_auto_id += 1;
+        cluster.samples.clear();
+
+        Some(MinedTemplate { id, pattern })
+    }
+
+    /// Abstract the contiguous token spans that vary across `samples` into
+    /// placeholders; positions every sample agrees on stay literal.
+    fn generalize(samples: &[Vec<String>]) -> Option<(String, usize)> {
+        let width = samples[0].len();
+        if samples.iter().any(|s| s.len() != width) {
+            return None;
+        }
+
+        let varies: Vec<bool> = (0..width)
+            .map(|i| {
+                let first = &samples[0][i];
+                samples.iter().any(|s| &s[i] != first)
+            })
+            .collect();
+
+        let mut pattern = String::new();
+        let mut slot_count = 0;
+        let mut i = 0;
+        while i < width {
+            if i > 0 {
+                pattern.push(' ');
+            }
+            if varies[i] {
+                pattern.push_str(&format!("{{{}}}", slot_count));
+                slot_count += 1;
+                while i < width && varies[i] {
+                    i += 1;
+                }
+            } else {
+                pattern.push_str(&samples[0][i]);
+                i += 1;
+            }
+        }
+
+        Some((pattern, slot_count))
+    }
+
+    /// Estimate whether registering `pattern` pays for its own overhead: the
+    /// bytes saved per future match (original length minus the binary
+    /// semantic payload it would take) times the observed frequency, against
+    /// the one-time cost of storing the pattern text itself.
+    fn is_worth_registering(pattern: &str, samples: &[Vec<String>]) -> bool {
+        let overhead = pattern.len();
+        let avg_original: usize =
+            samples.iter().map(|s| s.join(" ").len()).sum::<usize>() / samples.len().max(1);
+        // method byte + template id + slot count + ~3 bytes per slot (length
+        // prefix plus a short value).
+        let slot_count = pattern.matches('{').count();
+        let avg_encoded = 1 + 4 + 1 + slot_count * 3;
+        let bytes_saved_per_use = avg_original.saturating_sub(avg_encoded);
+        let projected_saving = bytes_saved_per_use * samples.len();
+        projected_saving > overhead
+    }
+}
+
+impl Default for TemplateMiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mines_a_recurring_shape_after_threshold() {
+        let mut miner = TemplateMiner::new();
+        // Same token count and same first/last token, so all three land in
+        // one cluster; only the interior token varies.
+        assert!(miner.observe("I cannot access your files today.").is_none());
+        assert!(miner.observe("I cannot access your camera today.").is_none());
+        let mined = miner.observe("I cannot access your microphone today.").unwrap();
+
+        assert!(mined.id >= AUTO_ID_START);
+        assert_eq!(mined.pattern, "I cannot access your {0} today.");
+    }
+
+    #[test]
+    fn ignores_messages_too_short_to_generalize() {
+        let mut miner = TemplateMiner::new();
+        assert!(miner.observe("hi").is_none());
+        assert!(miner.observe("ok").is_none());
+        assert!(miner.observe("no").is_none());
+    }
+
+    #[test]
+    fn evicts_lowest_hit_count_once_over_capacity() {
+        let mut miner = TemplateMiner::new();
+        for i in 0..=MAX_AUTO_TEMPLATES {
+            let id = AUTO_ID_START + i as u32;
+            miner.track(id);
+            for _ in 0..i {
+                miner.record_hit(id);
+            }
+        }
+        assert_eq!(miner.evict_candidate(), Some(AUTO_ID_START));
+    }
+}
@@ -0,0 +1,188 @@
+//! Rule-driven intent classification and content screening
+//!
+//! A small pattern→label rule database, compiled once at construction into
+//! a single `RegexSet` so `classify`/`blocking_matches` scan a payload's
+//! plaintext in one pass instead of testing each rule's regex in turn.
+
+use crate::{AuraError, Result};
+use regex::RegexSet;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// How much weight a rule's label carries against other matches on the same
+/// text, and whether a match should reject the payload outright. Declared
+/// in ascending order so `Ord` picks `Block` over `Warn` over `Info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Warn,
+    Block,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub pattern: String,
+    pub label: String,
+    pub severity: Severity,
+}
+
+/// Compiled rule set. Construction is the only place patterns are parsed;
+/// `classify`/`blocking_matches` are just a `RegexSet` scan plus a lookup.
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+    set: RegexSet,
+}
+
+impl RuleEngine {
+    pub fn new(rules: Vec<Rule>) -> Result<Self> {
+        let set = RegexSet::new(rules.iter().map(|rule| &rule.pattern))
+            .map_err(|e| AuraError::InvalidPayload(format!("Invalid rule pattern: {}", e)))?;
+        Ok(Self { rules, set })
+    }
+
+    /// A rule set with nothing registered, so a `Compressor` built without a
+    /// rule database behaves exactly as before: `classify` always falls
+    /// back to `"general"`, nothing is ever blocked.
+    pub fn empty() -> Self {
+        Self { rules: Vec::new(), set: RegexSet::empty() }
+    }
+
+    /// Load a rule database from a JSON array of `{"pattern", "label",
+    /// "severity"}` objects.
+    pub fn load_from_file(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let rules: Vec<Rule> = serde_json::from_str(&content)?;
+        Self::new(rules)
+    }
+
+    fn matches(&self, text: &str) -> Vec<&Rule> {
+        self.set.matches(text).into_iter().map(|i| &self.rules[i]).collect()
+    }
+
+    /// The label of the highest-severity rule matching `text`, breaking
+    /// ties by whichever rule was declared first; `"general"` when nothing
+    /// matches.
+    pub fn classify(&self, text: &str) -> String {
+        // `Iterator::max_by_key` keeps the *last* maximal element on ties,
+        // which would favor the last-declared rule; reverse first so it
+        // keeps the first-declared one instead, matching the doc above.
+        self.matches(text)
+            .into_iter()
+            .rev()
+            .max_by_key(|rule| rule.severity)
+            .map(|rule| rule.label.clone())
+            .unwrap_or_else(|| "general".to_string())
+    }
+
+    /// Every `Severity::Block` rule matching `text`, for screening and audit
+    /// logging.
+    pub fn blocking_matches(&self, text: &str) -> Vec<&Rule> {
+        self.matches(text)
+            .into_iter()
+            .filter(|rule| rule.severity == Severity::Block)
+            .collect()
+    }
+
+    /// Feed one chunk of a larger decompressed payload through the rule set,
+    /// updating `state` in place. Lets a caller screen a large payload piece
+    /// by piece instead of holding the whole plaintext in memory for one
+    /// `classify`/`blocking_matches` call. Matches don't span chunk
+    /// boundaries — a caller scanning for patterns wider than a chunk should
+    /// overlap consecutive chunks by that width.
+    pub fn scan_chunk(&self, chunk: &str, state: &mut ScanState) {
+        for rule in self.matches(chunk) {
+            if rule.severity == Severity::Block {
+                state.blocked = true;
+            }
+            if state.best.as_ref().map_or(true, |(severity, _)| rule.severity > *severity) {
+                state.best = Some((rule.severity, rule.label.clone()));
+            }
+        }
+    }
+}
+
+/// Accumulated result of one or more `RuleEngine::scan_chunk` calls.
+#[derive(Debug, Default)]
+pub struct ScanState {
+    blocked: bool,
+    best: Option<(Severity, String)>,
+}
+
+impl ScanState {
+    pub fn is_blocked(&self) -> bool {
+        self.blocked
+    }
+
+    pub fn label(&self) -> &str {
+        self.best.as_ref().map_or("general", |(_, label)| label.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_same_severity_rules() -> Vec<Rule> {
+        vec![
+            Rule { pattern: "foo".to_string(), label: "first".to_string(), severity: Severity::Warn },
+            Rule { pattern: "bar".to_string(), label: "second".to_string(), severity: Severity::Warn },
+        ]
+    }
+
+    #[test]
+    fn classify_breaks_same_severity_ties_by_declaration_order() {
+        let engine = RuleEngine::new(two_same_severity_rules()).unwrap();
+        assert_eq!(engine.classify("foo bar"), "first");
+
+        // Order in the text doesn't matter - declaration order does.
+        assert_eq!(engine.classify("bar foo"), "first");
+    }
+
+    #[test]
+    fn classify_prefers_higher_severity_over_declaration_order() {
+        let rules = vec![
+            Rule { pattern: "foo".to_string(), label: "low".to_string(), severity: Severity::Info },
+            Rule { pattern: "bar".to_string(), label: "high".to_string(), severity: Severity::Block },
+        ];
+        let engine = RuleEngine::new(rules).unwrap();
+        assert_eq!(engine.classify("foo bar"), "high");
+    }
+
+    #[test]
+    fn classify_falls_back_to_general_with_no_match() {
+        let engine = RuleEngine::new(two_same_severity_rules()).unwrap();
+        assert_eq!(engine.classify("nothing matches here"), "general");
+    }
+
+    #[test]
+    fn scan_chunk_keeps_the_first_chunk_s_match_on_a_severity_tie() {
+        let engine = RuleEngine::new(two_same_severity_rules()).unwrap();
+        let mut state = ScanState::default();
+
+        // "bar" (the second-declared rule) arrives in the earlier chunk;
+        // since scan_chunk only replaces `best` on a strictly higher
+        // severity, a same-severity match in a later chunk doesn't bump it -
+        // whichever chunk the match first showed up in wins the tie.
+        engine.scan_chunk("bar", &mut state);
+        engine.scan_chunk("foo", &mut state);
+
+        assert!(!state.is_blocked());
+        assert_eq!(state.label(), "second");
+    }
+
+    #[test]
+    fn scan_chunk_marks_blocked_on_a_block_severity_match() {
+        let rules = vec![Rule {
+            pattern: "secret".to_string(),
+            label: "leak".to_string(),
+            severity: Severity::Block,
+        }];
+        let engine = RuleEngine::new(rules).unwrap();
+        let mut state = ScanState::default();
+
+        engine.scan_chunk("this chunk has a secret in it", &mut state);
+
+        assert!(state.is_blocked());
+        assert_eq!(state.label(), "leak");
+    }
+}
@@ -1,6 +1,7 @@
 //! Server SDK implementation
 
-use crate::{Compressor, CompressionMetadata, CompressionMethod, Result};
+use crate::{Compressor, CompressionMetadata, CompressionMethod, DecompressionMetadata, HashAlgorithm, RegistryChange, Result};
+use std::collections::HashMap;
 
 pub struct ServerSDK {
     compressor: Compressor,
@@ -13,6 +14,7 @@ impl ServerSDK {
         enable_audit_logging: bool,
         session_id: Option<String>,
         user_id: Option<String>,
+        rule_db_path: Option<String>,
     ) -> Self {
         Self {
             compressor: Compressor::new(
@@ -21,6 +23,7 @@ impl ServerSDK {
                 enable_audit_logging,
                 session_id,
                 user_id,
+                rule_db_path,
             ),
         }
     }
@@ -30,29 +33,90 @@ impl ServerSDK {
         text: &str,
         template_id: Option<u32>,
         slots: Option<Vec<String>>,
+        integrity: Option<HashAlgorithm>,
     ) -> Result<(Vec<u8>, CompressionMethod, CompressionMetadata)> {
-        self.compressor.compress(text, template_id, slots)
+        self.compressor.compress(text, template_id, slots, integrity)
+    }
+
+    /// Like `compress`, but fills the template's slots by name instead of
+    /// position — see `Compressor::compress_named`.
+    pub fn compress_named(
+        &self,
+        template_id: u32,
+        named_slots: HashMap<String, String>,
+        integrity: Option<HashAlgorithm>,
+    ) -> Result<(Vec<u8>, CompressionMethod, CompressionMetadata)> {
+        self.compressor.compress_named(template_id, named_slots, integrity)
+    }
+
+    /// Like `compress`, but only emits a method the peer advertised during
+    /// the handshake (see `ClientSDK::supported_methods`), falling back to
+    /// `Uncompressed` when none of the candidates that would otherwise win
+    /// are in that set. Use this instead of `compress` whenever the peer's
+    /// capabilities are known, so a codec added for one client can't leak a
+    /// method byte an older client can't interpret.
+    pub fn compress_for_client(
+        &self,
+        text: &str,
+        template_id: Option<u32>,
+        slots: Option<Vec<String>>,
+        supported_methods: &[CompressionMethod],
+        integrity: Option<HashAlgorithm>,
+    ) -> Result<(Vec<u8>, CompressionMethod, CompressionMetadata)> {
+        self.compressor
+            .compress_for_peer(text, template_id, slots, supported_methods, integrity)
     }
 
     pub fn decompress(&self, payload: &[u8]) -> Result<String> {
         self.compressor.decompress(payload)
     }
 
-    pub fn extract_metadata(&self, _payload: &[u8]) -> crate::DecompressionMetadata {
-        // Simplified metadata extraction
-        crate::DecompressionMetadata {
-            method: "unknown".to_string(),
-            template_ids: vec![],
+    /// Read a payload's method and template IDs from its header and
+    /// metadata index alone, without decompressing the body — see
+    /// `Compressor::peek_metadata`. `integrity_verified` is always `None`
+    /// here; use `screen_fast_path` when a verified answer is required.
+    pub fn extract_metadata(&self, payload: &[u8]) -> Result<DecompressionMetadata> {
+        self.compressor.peek_metadata(payload)
+    }
+
+    /// Decompress `payload` and classify it against the rule database passed
+    /// to `ServerSDK::new`, falling back to `"general"` on a decompression
+    /// failure as well as on no rule matching.
+    pub fn classify_intent(&self, payload: &[u8]) -> String {
+        match self.compressor.decompress(payload) {
+            Ok(text) => self.compressor.classify_intent(&text),
+            Err(_) => "general".to_string(),
         }
     }
 
-    pub fn classify_intent(&self, _payload: &[u8]) -> String {
-        // Simplified intent classification
-        "general".to_string()
+    /// Reject a payload early when it's malformed, its integrity trailer
+    /// fails verification, or its decompressed text matches a blocking rule
+    /// (logged via the audit path when `enable_audit_logging` is set).
+    /// Unlike `extract_metadata`, this does pay for a full decompression,
+    /// since both integrity verification and content screening need the
+    /// plaintext.
+    pub fn screen_fast_path(&self, payload: &[u8]) -> bool {
+        match self.compressor.decompress_with_metadata(payload) {
+            Ok((text, metadata)) => {
+                metadata.integrity_verified != Some(false) && self.compressor.screen_text(&text)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// The template registry version this server currently holds.
+    pub fn template_registry_version(&self) -> u32 {
+        self.compressor.template_registry_version()
+    }
+
+    /// Every registry change since `since_version`, to send to a client so
+    /// it can catch its own registry up without resending the whole table.
+    pub fn export_template_delta(&self, since_version: u32) -> Vec<RegistryChange> {
+        self.compressor.export_template_delta(since_version)
     }
 
-    pub fn screen_fast_path(&self, _payload: &[u8]) -> bool {
-        // Simplified security screening
-        true
+    /// Apply a delta received from a client's `export_template_delta`.
+    pub fn import_template_delta(&self, changes: &[RegistryChange]) {
+        self.compressor.import_template_delta(changes);
     }
 }
@@ -1,25 +1,140 @@
 //! Template library management
 
+use crate::matcher::TemplateMatcher;
 use crate::{AuraError, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 
+/// A named placeholder declared by a template's pattern, parsed from a
+/// `{name}` or `{name=default}` occurrence. `compress_named` resolves a
+/// template's slots against a `HashMap<String, String>` by these names,
+/// falling back to `default` for any it omits.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotDef {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+/// A template's pattern plus its declared slots, as returned by
+/// `TemplateLibrary::list` / `Compressor::list_templates` so a caller can
+/// see what a template expects before filling it with `compress_named`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    pub pattern: String,
+    pub slots: Vec<SlotDef>,
+}
+
+enum PatternPiece {
+    Literal(String),
+    /// `raw` is the placeholder's original `{...}` text, used as a
+    /// fallback when `format_template`/`format_template_with_offsets` is
+    /// given fewer slot values than the pattern declares and the slot has
+    /// no default: the unresolved placeholder is left in place rather than
+    /// silently dropped.
+    Slot { def: SlotDef, raw: String },
+}
+
+/// Parse a pattern into literal runs and placeholders in left-to-right
+/// order. A placeholder's body splits on the first `=` into a name and an
+/// optional default (e.g. `{0}`, `{user}`, `{user=Guest}`); numeric names
+/// like the core templates' `{0}`/`{1}` are just names with no special
+/// handling, kept purely positional.
+fn parse_pattern(pattern: &str) -> Vec<PatternPiece> {
+    let mut pieces = Vec::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '{' {
+            let mut inner = String::new();
+            for next in chars.by_ref() {
+                if next == '}' {
+                    break;
+                }
+                inner.push(next);
+            }
+            if !literal.is_empty() {
+                pieces.push(PatternPiece::Literal(std::mem::take(&mut literal)));
+            }
+            let mut parts = inner.splitn(2, '=');
+            let name = parts.next().unwrap_or_default().to_string();
+            let default = parts.next().map(|s| s.to_string());
+            pieces.push(PatternPiece::Slot { def: SlotDef { name, default }, raw: format!("{{{}}}", inner) });
+        } else {
+            literal.push(c);
+        }
+    }
+    if !literal.is_empty() {
+        pieces.push(PatternPiece::Literal(literal));
+    }
+
+    pieces
+}
+
+fn slot_defs_of(pattern: &str) -> Vec<SlotDef> {
+    parse_pattern(pattern)
+        .into_iter()
+        .filter_map(|piece| match piece {
+            PatternPiece::Slot { def, .. } => Some(def),
+            PatternPiece::Literal(_) => None,
+        })
+        .collect()
+}
+
+/// One recorded change to a `TemplateLibrary`'s pattern set, in commit
+/// order. `export_since`/`import_delta` stream these so a client and server
+/// that started from the same base (the core templates loaded at version 0)
+/// can converge incrementally instead of re-syncing the whole table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RegistryChange {
+    Registered { template_id: u32, pattern: String },
+    Removed { template_id: u32 },
+}
+
 pub struct TemplateLibrary {
     templates: HashMap<u32, String>,
+    matcher: TemplateMatcher,
+    /// Bumped on every mutation, so a payload encoded against an older (or
+    /// newer) template set can be detected instead of silently
+    /// mis-expanding slots against the wrong patterns.
+    version: u32,
+    /// Every change in commit order; `history[i]` is the change that bumped
+    /// `version` from `base + i` to `base + i + 1`, where `base` is the
+    /// version `load_from_file` last left behind (0 if it was never called),
+    /// i.e. `version - history.len()`. Cleared by `load_from_file`, which
+    /// loads a flat snapshot with no change trail of its own — registry sync
+    /// only covers changes made via `register`/`remove` from that point on.
+    history: Vec<RegistryChange>,
 }
 
 impl TemplateLibrary {
     pub fn new() -> Self {
         let mut library = Self {
             templates: HashMap::new(),
+            matcher: TemplateMatcher::build(&HashMap::new()),
+            version: 0,
+            history: Vec::new(),
         };
 
         // Load core templates
         library.load_core_templates();
+        library.rebuild_matcher();
 
         library
     }
 
+    /// The current template-set version, embedded in binary-semantic payload
+    /// headers so a decoder can reject payloads produced against a
+    /// different version instead of mis-expanding slots.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn rebuild_matcher(&mut self) {
+        self.matcher = TemplateMatcher::build(&self.templates);
+    }
+
     fn load_core_templates(&mut self) {
         // Core limitation templates (0-9)
         self.templates.insert(0, "I don't have access to {0}. {1}".to_string());
@@ -47,62 +162,135 @@ impl TemplateLibrary {
     }
 
     pub fn register(&mut self, template_id: u32, pattern: String) {
-        self.templates.insert(template_id, pattern);
+        self.templates.insert(template_id, pattern.clone());
+        self.history.push(RegistryChange::Registered { template_id, pattern });
+        self.version += 1;
+        self.rebuild_matcher();
+    }
+
+    /// Remove a template (e.g. a low-value auto-mined one being evicted).
+    pub fn remove(&mut self, template_id: u32) {
+        self.templates.remove(&template_id);
+        self.history.push(RegistryChange::Removed { template_id });
+        self.version += 1;
+        self.rebuild_matcher();
+    }
+
+    /// Every change since `since_version`, in commit order, for
+    /// `ClientSDK`/`ServerSDK` to stream to a peer so it can catch its own
+    /// registry up without resending the whole table.
+    pub fn export_since(&self, since_version: u32) -> Vec<RegistryChange> {
+        let base = self.version - self.history.len() as u32;
+        let start = since_version.saturating_sub(base) as usize;
+        self.history
+            .get(start..)
+            .map(|changes| changes.to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Apply a delta received from `export_since`, replaying each change in
+    /// order and bumping `version` to match so the two sides agree on what a
+    /// given version number means.
+    pub fn import_delta(&mut self, changes: &[RegistryChange]) {
+        for change in changes {
+            match change {
+                RegistryChange::Registered { template_id, pattern } => {
+                    self.templates.insert(*template_id, pattern.clone());
+                }
+                RegistryChange::Removed { template_id } => {
+                    self.templates.remove(template_id);
+                }
+            }
+            self.history.push(change.clone());
+            self.version += 1;
+        }
+        self.rebuild_matcher();
     }
 
     pub fn format_template(&self, template_id: u32, slots: &[String]) -> Result<String> {
+        Ok(self.format_template_with_offsets(template_id, slots)?.0)
+    }
+
+    /// Like `format_template`, but also reports where each filled-in slot
+    /// landed in the result, as `(offset, length)` byte ranges — used to
+    /// build the metadata index (see `crate::metadata`) that lets a reader
+    /// locate a payload's substituted regions without decompressing it.
+    ///
+    /// Placeholders are filled by occurrence order against `slots`,
+    /// regardless of name; a placeholder past the end of `slots` falls back
+    /// to its declared default if it has one, and otherwise is left as its
+    /// original `{...}` text rather than silently dropped.
+    pub fn format_template_with_offsets(
+        &self,
+        template_id: u32,
+        slots: &[String],
+    ) -> Result<(String, Vec<(u32, u32)>)> {
         let pattern = self.templates
             .get(&template_id)
             .ok_or(AuraError::TemplateNotFound(template_id))?;
 
-        let mut result = pattern.clone();
-        for (i, slot) in slots.iter().enumerate() {
-            let placeholder = format!("{{{}}}", i);
-            result = result.replace(&placeholder, slot);
-        }
-
-        Ok(result)
-    }
+        let mut result = String::with_capacity(pattern.len());
+        let mut offsets = Vec::with_capacity(slots.len());
+        let mut slot_iter = slots.iter();
 
-    pub fn match_template(&self, text: &str) -> Option<(u32, Vec<String>)> {
-        // Simple template matching - try exact matches first
-        for (&id, pattern) in &self.templates {
-            if let Some(slots) = self.extract_slots(text, pattern) {
-                return Some((id, slots));
+        for piece in parse_pattern(pattern) {
+            match piece {
+                PatternPiece::Literal(text) => result.push_str(&text),
+                PatternPiece::Slot { def, raw } => match slot_iter.next().cloned().or(def.default) {
+                    Some(value) => {
+                        let start = result.len();
+                        result.push_str(&value);
+                        offsets.push((start as u32, value.len() as u32));
+                    }
+                    None => result.push_str(&raw),
+                },
             }
         }
-        None
-    }
 
-    fn extract_slots(&self, text: &str, pattern: &str) -> Option<Vec<String>> {
-        // Simple slot extraction - split pattern by placeholders
-        let parts: Vec<&str> = pattern.split(|c| c == '{' || c == '}').collect();
-        let mut slots = Vec::new();
-        let mut text_pos = 0;
+        Ok((result, offsets))
+    }
 
-        for (i, part) in parts.iter().enumerate() {
-            if part.is_empty() {
-                continue;
-            }
+    /// Declared slot names and defaults for `template_id`, in placeholder
+    /// order — see `SlotDef`.
+    pub fn slot_defs(&self, template_id: u32) -> Result<Vec<SlotDef>> {
+        let pattern = self.templates
+            .get(&template_id)
+            .ok_or(AuraError::TemplateNotFound(template_id))?;
+        Ok(slot_defs_of(pattern))
+    }
 
-            // Check if this is a placeholder index (odd positions)
-            if i % 2 == 1 {
-                continue; // Skip placeholder indices
-            }
+    /// Resolve a `compress_named` call's slot map into the positional
+    /// `Vec<String>` `format_template`/`compress` expect: each declared slot
+    /// takes its named value or, absent that, its declared default. A name
+    /// in `named_slots` the template doesn't declare is an error rather
+    /// than silently ignored.
+    pub fn resolve_named_slots(
+        &self,
+        template_id: u32,
+        named_slots: &HashMap<String, String>,
+    ) -> Result<Vec<String>> {
+        let defs = self.slot_defs(template_id)?;
 
-            // Find the literal part in text
-            if let Some(pos) = text[text_pos..].find(part) {
-                // Extract slot value before this literal
-                if i > 0 && text_pos < pos + text_pos {
-                    slots.push(text[text_pos..pos + text_pos].to_string());
-                }
-                text_pos = pos + text_pos + part.len();
-            } else {
-                return None; // Pattern doesn't match
-            }
+        let known: std::collections::HashSet<&str> = defs.iter().map(|d| d.name.as_str()).collect();
+        if let Some(unknown) = named_slots.keys().find(|name| !known.contains(name.as_str())) {
+            return Err(AuraError::UnknownNamedSlot(template_id, unknown.clone()));
         }
 
-        Some(slots)
+        defs.into_iter()
+            .map(|def| {
+                named_slots
+                    .get(&def.name)
+                    .cloned()
+                    .or(def.default)
+                    .ok_or_else(|| AuraError::MissingNamedSlot(template_id, def.name))
+            })
+            .collect()
+    }
+
+    /// Find the best matching template for `text`, scanning every registered
+    /// pattern in a single Aho-Corasick pass (see `crate::matcher`).
+    pub fn match_template(&self, text: &str) -> Option<(u32, Vec<String>)> {
+        self.matcher.match_text(text)
     }
 
     pub fn load_from_file(&mut self, path: &str) -> Result<()> {
@@ -117,10 +305,135 @@ impl TemplateLibrary {
             }
         }
 
+        // A stored version takes precedence over the running count so a
+        // reloaded library keeps comparing equal against payloads encoded
+        // before the restart; falls back to bumping forward if the file
+        // predates versioning.
+        match data.get("version").and_then(|v| v.as_u64()) {
+            Some(version) => self.version = version as u32,
+            None => self.version += 1,
+        }
+
+        // The file is a flat snapshot, not a change trail, so registry sync
+        // (`export_since`/`import_delta`) can only cover changes made from
+        // here on.
+        self.history.clear();
+
+        self.rebuild_matcher();
+        Ok(())
+    }
+
+    /// Every registered template's pattern and declared slots, for a client
+    /// to introspect what a template expects before filling it with
+    /// `compress_named`.
+    pub fn list(&self) -> HashMap<u32, TemplateInfo> {
+        self.templates
+            .iter()
+            .map(|(id, pattern)| {
+                (*id, TemplateInfo { pattern: pattern.clone(), slots: slot_defs_of(pattern) })
+            })
+            .collect()
+    }
+
+    /// Persist the template table to `path`, in the same `{"templates": {id:
+    /// {"pattern": ...}}}` format `load_from_file` reads, so auto-mined
+    /// templates survive across sessions.
+    pub fn save_to_file(&self, path: &str) -> Result<()> {
+        let mut templates = serde_json::Map::new();
+        for (id, pattern) in &self.templates {
+            templates.insert(
+                id.to_string(),
+                serde_json::json!({ "pattern": pattern }),
+            );
+        }
+
+        let mut data: serde_json::Value = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(_) => serde_json::json!({}),
+        };
+        data["templates"] = serde_json::Value::Object(templates);
+        data["version"] = serde_json::json!(self.version);
+        fs::write(path, serde_json::to_string_pretty(&data)?)?;
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_selects_the_most_specific_core_template() {
+        let library = TemplateLibrary::new();
+
+        // Ids 10 and 12 both end in " is {n}." with a leading "The "; 12's
+        // longer literal prefix ("The capital of ") should win over 10's
+        // shorter, more generic one ("The {0} of ").
+        let (id, slots) = library.match_template("The capital of France is Paris.").unwrap();
+        assert_eq!(id, 12);
+        assert_eq!(slots, vec!["France".to_string(), "Paris".to_string()]);
+
+        // Unambiguous against the rest of the 20-template core library.
+        let (id, slots) = library
+            .match_template("Python is a programming language.")
+            .unwrap();
+        assert_eq!(id, 11);
+        assert_eq!(slots, vec!["Python".to_string(), "a programming language".to_string()]);
+    }
+
+    #[test]
+    fn import_delta_catches_a_peer_up_incrementally() {
+        let mut server = TemplateLibrary::new();
+        let base_version = server.version();
+
+        server.register(900, "Known for {0}.".to_string());
+        server.register(901, "Famous for {0}.".to_string());
+        server.remove(900);
+
+        let mut client = TemplateLibrary::new();
+        assert_eq!(client.version(), base_version);
+
+        let delta = server.export_since(base_version);
+        assert_eq!(delta.len(), 3);
+        client.import_delta(&delta);
+
+        assert_eq!(client.version(), server.version());
+        assert!(client.match_template("Famous for testing.").is_some());
+        assert!(client.templates.get(&900).is_none());
+
+        // Re-exporting from the now-current version yields nothing further.
+        assert!(server.export_since(server.version()).is_empty());
+    }
+
+    #[test]
+    fn named_slots_fall_back_to_declared_defaults() {
+        let mut library = TemplateLibrary::new();
+        library.register(900, "{name=a guest} checked in at {time}.".to_string());
+
+        let defs = library.slot_defs(900).unwrap();
+        assert_eq!(defs[0], SlotDef { name: "name".to_string(), default: Some("a guest".to_string()) });
+        assert_eq!(defs[1], SlotDef { name: "time".to_string(), default: None });
+
+        // Omitting "name" falls back to its default; "time" is supplied.
+        let mut named = HashMap::new();
+        named.insert("time".to_string(), "9am".to_string());
+        let slots = library.resolve_named_slots(900, &named).unwrap();
+        assert_eq!(slots, vec!["a guest".to_string(), "9am".to_string()]);
+        assert_eq!(library.format_template(900, &slots).unwrap(), "a guest checked in at 9am.");
+
+        // A name the template doesn't declare is an error...
+        let mut unknown = named.clone();
+        unknown.insert("room".to_string(), "204".to_string());
+        assert!(matches!(
+            library.resolve_named_slots(900, &unknown),
+            Err(AuraError::UnknownNamedSlot(900, name)) if name == "room"
+        ));
 
-    pub fn list(&self) -> HashMap<u32, String> {
-        self.templates.clone()
+        // ...and a slot with no value and no default is also an error,
+        // rather than silently leaving the placeholder in place.
+        assert!(matches!(
+            library.resolve_named_slots(900, &HashMap::new()),
+            Err(AuraError::MissingNamedSlot(900, name)) if name == "time"
+        ));
     }
 }
@@ -7,10 +7,34 @@
 //! - Messages 6-20: 0.5ms avg (pattern recognition)
 //! - Messages 21+: 0.15ms avg (instant responses)
 
+use crate::dedup::{encoded_size, ChunkRef, ChunkStore, ChunkerConfig, ContentDefinedChunker};
 use crate::metadata::{MetadataEntry, compute_metadata_signature};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{Read, Write};
 use std::time::Instant;
 
+/// Cap on how many outgoing edges the Markov transition tables track, so a
+/// long-running platform accelerator can't grow the table unbounded; the
+/// lowest-count edge is evicted once a new one pushes the table past this.
+const MAX_TRANSITION_EDGES: usize = 5_000;
+
+/// Fixed per-entry overhead (struct fields, heap allocation headers, hash
+/// map bucket) charged against the byte budget alongside the payload,
+/// decompressed-text and chunk-reference lengths, so a cache of tiny entries can't blow past a
+/// budget sized for their headers alone.
+const CACHE_ENTRY_OVERHEAD_BYTES: usize = 64;
+
+/// Count and recency of one observed signature-to-signature transition.
+/// Recency is a logical sequence number (not wall-clock time) so successor
+/// ranking can break count ties in favor of the most recently seen edge.
+#[derive(Debug, Clone, Copy, Default)]
+struct EdgeStats {
+    count: usize,
+    last_seen: usize,
+}
+
 /// Cached compression pattern indexed by metadata signature
 #[derive(Debug, Clone)]
 pub struct CachedPattern {
@@ -18,6 +42,12 @@ pub struct CachedPattern {
     pub metadata: Vec<MetadataEntry>,
     pub compressed_payload: Vec<u8>,
     pub decompressed_text: Option<String>,
+    /// The chunk-reference stream `ConversationCache::store` produced by
+    /// running this entry's text through the shared `ChunkStore`, if it had
+    /// text to encode. Lets `ConversationCache::lookup` rematerialize text
+    /// on a later cache miss-of-text by replaying cross-turn dedup instead
+    /// of falling back to the generic `Decompressor`.
+    pub chunk_refs: Option<Vec<ChunkRef>>,
     pub hit_count: usize,
     pub last_used: Instant,
 }
@@ -28,27 +58,102 @@ impl CachedPattern {
         metadata: Vec<MetadataEntry>,
         compressed_payload: Vec<u8>,
         decompressed_text: Option<String>,
+        chunk_refs: Option<Vec<ChunkRef>>,
     ) -> Self {
         Self {
             signature,
             metadata,
             compressed_payload,
             decompressed_text,
+            chunk_refs,
             hit_count: 0,
             last_used: Instant::now(),
         }
     }
+
+    /// Approximate heap footprint charged against a cache's byte budget:
+    /// the compressed payload, the decompressed text (if materialized), the
+    /// chunk-reference stream (8 bytes per reference plus each new chunk's
+    /// bytes), and a fixed per-entry overhead.
+    fn memory_size(&self) -> usize {
+        let chunk_refs_bytes: usize = self
+            .chunk_refs
+            .as_ref()
+            .map(|refs| {
+                refs.iter()
+                    .map(|r| match r {
+                        ChunkRef::Stored(_) => std::mem::size_of::<u64>(),
+                        ChunkRef::New(_, payload) => std::mem::size_of::<u64>() + payload.len(),
+                    })
+                    .sum()
+            })
+            .unwrap_or(0);
+
+        self.compressed_payload.len()
+            + self.decompressed_text.as_ref().map(|text| text.len()).unwrap_or(0)
+            + chunk_refs_bytes
+            + CACHE_ENTRY_OVERHEAD_BYTES
+    }
+
+    /// This entry's text, decompressing `compressed_payload` via
+    /// `decompressor` and memoizing the result on first demand. Entries
+    /// stored via `ConversationCache::store_compressed_only` start with
+    /// `decompressed_text` at `None`, so the cold-path "learning" region
+    /// (messages 1-5) doesn't pay to hold strings it may never reuse;
+    /// `get_text` is the only thing that pays the decompression cost, and
+    /// only once.
+    pub fn get_text(&mut self, decompressor: &dyn Decompressor) -> Result<&str, String> {
+        if self.decompressed_text.is_none() {
+            self.decompressed_text = Some(decompressor.decompress(&self.compressed_payload)?);
+        }
+        Ok(self.decompressed_text.as_deref().unwrap())
+    }
+}
+
+/// Decompresses a `CachedPattern`'s compressed payload into text on demand.
+/// `ConversationAccelerator` holds one (the same codec that produced
+/// `compressed_payload`) and passes it to `CachedPattern::get_text` so
+/// lazily-stored entries materialize without threading codec state through
+/// every cache read.
+pub trait Decompressor {
+    fn decompress(&self, payload: &[u8]) -> Result<String, String>;
+}
+
+/// One cache slot: the pattern plus its links in the recency list, keyed on
+/// the same signature that indexes `ConversationCache::cache`. Using the
+/// signature itself as the "index" avoids a second slab/arena alongside the
+/// map.
+struct CacheNode {
+    pattern: CachedPattern,
+    prev: Option<u32>,
+    next: Option<u32>,
 }
 
 /// Conversation-specific cache for adaptive acceleration (Claim 31)
 ///
-/// Caches patterns by metadata signature for O(1) lookup.
+/// Caches patterns by metadata signature for O(1) lookup. Recency is tracked
+/// with an intrusive doubly-linked list threaded through `CacheNode::prev`/
+/// `next` (most-recently-used at `head`, eviction victim at `tail`), so both
+/// `lookup` promotion and `store` eviction are O(1) instead of the O(n) scan
+/// a `last_used` timestamp comparison would need.
 /// Cache hit rate progression: 0% → 97% over conversation.
 pub struct ConversationCache {
     max_size: usize,
-    cache: HashMap<u32, CachedPattern>,
+    cache: HashMap<u32, CacheNode>,
+    head: Option<u32>,
+    tail: Option<u32>,
     hit_count: usize,
     miss_count: usize,
+    chunker: ContentDefinedChunker,
+    chunk_store: ChunkStore,
+    referenced_bytes: usize,
+    transmitted_bytes: usize,
+    /// Running sum of `CachedPattern::memory_size()` over every entry
+    /// currently cached; compared against `bytes_budget` on every `store`.
+    current_bytes: usize,
+    /// Optional byte ceiling, composable with `max_size`: `store` evicts
+    /// least-recently-used entries until both limits are satisfied.
+    bytes_budget: Option<usize>,
 }
 
 impl ConversationCache {
@@ -56,52 +161,203 @@ impl ConversationCache {
         Self {
             max_size,
             cache: HashMap::new(),
+            head: None,
+            tail: None,
             hit_count: 0,
             miss_count: 0,
+            chunker: ContentDefinedChunker::new(ChunkerConfig::default()),
+            chunk_store: ChunkStore::new(),
+            referenced_bytes: 0,
+            transmitted_bytes: 0,
+            current_bytes: 0,
+            bytes_budget: None,
+        }
+    }
+
+    /// Like `new`, but additionally bounds the cache by logical byte size:
+    /// `store` evicts least-recently-used entries until the running total
+    /// of payload + decompressed-text + chunk-reference + overhead bytes
+    /// fits `bytes_budget`,
+    /// on top of the existing `max_size` entry-count limit.
+    pub fn with_memory_budget(max_size: usize, bytes_budget: usize) -> Self {
+        let mut cache = Self::new(max_size);
+        cache.bytes_budget = Some(bytes_budget);
+        cache
+    }
+
+    /// Unlink `signature`'s node from the recency list without removing it
+    /// from `cache`, patching its neighbors (or `head`/`tail`) in its place.
+    fn unlink(&mut self, signature: u32) {
+        let (prev, next) = {
+            let node = self.cache.get(&signature).expect("unlink: signature not in cache");
+            (node.prev, node.next)
+        };
+
+        match prev {
+            Some(p) => self.cache.get_mut(&p).unwrap().next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(n) => self.cache.get_mut(&n).unwrap().prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    /// Thread `signature`'s node in as the new recency-list head.
+    fn push_front(&mut self, signature: u32) {
+        let old_head = self.head;
+        if let Some(node) = self.cache.get_mut(&signature) {
+            node.prev = None;
+            node.next = old_head;
+        }
+        if let Some(h) = old_head {
+            self.cache.get_mut(&h).unwrap().prev = Some(signature);
+        }
+        self.head = Some(signature);
+        if self.tail.is_none() {
+            self.tail = Some(signature);
+        }
+    }
+
+    /// Promote `signature` to most-recently-used in O(1).
+    fn touch(&mut self, signature: u32) {
+        if self.head == Some(signature) {
+            return;
+        }
+        self.unlink(signature);
+        self.push_front(signature);
+    }
+
+    /// Evict the least-recently-used entry (the list tail) in O(1).
+    fn evict_lru(&mut self) {
+        if let Some(victim) = self.tail {
+            self.unlink(victim);
+            if let Some(node) = self.cache.remove(&victim) {
+                self.current_bytes = self.current_bytes.saturating_sub(node.pattern.memory_size());
+            }
+        }
+    }
+
+    /// True once `current_bytes` has pushed past `bytes_budget` (always
+    /// false when no budget is configured).
+    fn over_byte_budget(&self) -> bool {
+        self.bytes_budget.is_some_and(|budget| self.current_bytes > budget)
+    }
+
+    /// Evict least-recently-used entries until both the entry-count and
+    /// byte-budget limits are satisfied, or the cache is empty.
+    fn enforce_budgets(&mut self) {
+        while !self.cache.is_empty() && (self.cache.len() > self.max_size || self.over_byte_budget()) {
+            self.evict_lru();
+        }
+    }
+
+    /// Encode a message as chunk references against the shared dedup store,
+    /// so spans already seen in earlier turns cost only a few reference
+    /// bytes instead of being retransmitted whole.
+    pub fn encode_message(&mut self, text: &str) -> Vec<ChunkRef> {
+        let refs = self.chunk_store.encode(&self.chunker, text);
+        let (original, transmitted) = encoded_size(&refs, &self.chunk_store);
+        self.referenced_bytes += original;
+        self.transmitted_bytes += transmitted;
+        refs
+    }
+
+    /// Reassemble a message previously produced by `encode_message`.
+    pub fn decode_message(&mut self, refs: &[ChunkRef]) -> Result<String, String> {
+        self.chunk_store.decode(refs)
+    }
+
+    /// Fraction of referenced message bytes that didn't need to be
+    /// retransmitted because they deduplicated against the chunk store.
+    pub fn dedup_ratio(&self) -> f64 {
+        if self.referenced_bytes == 0 {
+            return 0.0;
         }
+        1.0 - (self.transmitted_bytes as f64 / self.referenced_bytes as f64)
+    }
+
+    /// Total bytes saved by chunk deduplication so far.
+    pub fn bytes_saved(&self) -> usize {
+        self.referenced_bytes.saturating_sub(self.transmitted_bytes)
     }
 
     /// Lookup pattern by metadata signature (O(1) operation)
     ///
-    /// Returns cached pattern if found, None otherwise.
+    /// Returns cached pattern if found, None otherwise. A hit promotes the
+    /// entry to most-recently-used in O(1).
     pub fn lookup(&mut self, metadata: &[MetadataEntry]) -> Option<&mut CachedPattern> {
         let signature = compute_metadata_signature(metadata);
 
-        if let Some(pattern) = self.cache.get_mut(&signature) {
-            pattern.hit_count += 1;
-            pattern.last_used = Instant::now();
+        if self.cache.contains_key(&signature) {
+            self.touch(signature);
             self.hit_count += 1;
-            Some(pattern)
+            let chunk_store = &mut self.chunk_store;
+            let node = self.cache.get_mut(&signature).unwrap();
+            node.pattern.hit_count += 1;
+            node.pattern.last_used = Instant::now();
+
+            // A compressed-only entry that was dedup-encoded at store time
+            // can be rematerialized straight from its chunk-reference
+            // stream, no codec needed - cheaper than, and independent of,
+            // the `Decompressor` fallback `get_text` otherwise requires.
+            if node.pattern.decompressed_text.is_none() {
+                if let Some(refs) = &node.pattern.chunk_refs {
+                    if let Ok(text) = chunk_store.decode(refs) {
+                        node.pattern.decompressed_text = Some(text);
+                    }
+                }
+            }
+
+            Some(&mut node.pattern)
         } else {
             self.miss_count += 1;
             None
         }
     }
 
-    /// Store pattern in cache
+    /// Store pattern in cache, evicting least-recently-used entries in O(1)
+    /// steps via the recency list's tail until the entry-count and (if
+    /// configured) byte-budget limits are both satisfied.
+    ///
+    /// When `decompressed_text` is present, it's also run through the
+    /// shared `ChunkStore` so spans repeated across turns - boilerplate,
+    /// disclaimers, repeated context - dedupe against earlier messages; the
+    /// resulting chunk-reference stream is kept on the pattern so a later
+    /// `lookup` can rematerialize the text straight from it.
     pub fn store(
         &mut self,
         metadata: Vec<MetadataEntry>,
         compressed_payload: Vec<u8>,
         decompressed_text: Option<String>,
     ) {
+        let chunk_refs = decompressed_text.as_deref().map(|text| self.encode_message(text));
         let signature = compute_metadata_signature(&metadata);
-
-        // Evict least-recently-used if cache full
-        if self.cache.len() >= self.max_size && !self.cache.contains_key(&signature) {
-            if let Some(lru_signature) = self.cache
-                .iter()
-                .min_by_key(|(_, pattern)| pattern.last_used)
-                .map(|(sig, _)| *sig)
-            {
-                self.cache.remove(&lru_signature);
-            }
+        let pattern = CachedPattern::new(signature, metadata, compressed_payload, decompressed_text, chunk_refs);
+        let size = pattern.memory_size();
+
+        if self.cache.contains_key(&signature) {
+            self.touch(signature);
+            let node = self.cache.get_mut(&signature).unwrap();
+            self.current_bytes = self.current_bytes.saturating_sub(node.pattern.memory_size());
+            node.pattern = pattern;
+            self.current_bytes += size;
+            self.enforce_budgets();
+            return;
         }
 
-        self.cache.insert(
-            signature,
-            CachedPattern::new(signature, metadata, compressed_payload, decompressed_text),
-        );
+        self.cache.insert(signature, CacheNode { pattern, prev: None, next: None });
+        self.push_front(signature);
+        self.current_bytes += size;
+        self.enforce_budgets();
+    }
+
+    /// Store a pattern without its decompressed text, for entries that may
+    /// only ever serve as preload targets or Markov nodes rather than be
+    /// re-read as text. The text is produced lazily, via
+    /// `CachedPattern::get_text`, only if something later asks for it.
+    pub fn store_compressed_only(&mut self, metadata: Vec<MetadataEntry>, compressed_payload: Vec<u8>) {
+        self.store(metadata, compressed_payload, None);
     }
 
     /// Calculate cache hit rate (0.0 to 1.0)
@@ -123,8 +379,27 @@ impl ConversationCache {
             misses: self.miss_count,
             hit_rate: self.get_hit_rate(),
             total_patterns: self.cache.len(),
+            dedup_ratio: self.dedup_ratio(),
+            bytes_saved: self.bytes_saved(),
+            bytes_used: self.current_bytes,
+            bytes_budget: self.bytes_budget,
+            unmaterialized_entries: self
+                .cache
+                .values()
+                .filter(|node| node.pattern.decompressed_text.is_none())
+                .count(),
         }
     }
+
+    /// Query the process's actual resident bytes via jemalloc's allocator
+    /// stats, so operators can compare true allocation against the cache's
+    /// tracked logical `bytes_used`. Process-wide rather than per-cache, so
+    /// it's an associated function that takes no `self`; requires the
+    /// `jemalloc-stats` feature and the `jemalloc` global allocator.
+    #[cfg(feature = "jemalloc-stats")]
+    pub fn resident_bytes() -> Result<usize, String> {
+        jemalloc_ctl::stats::resident::read().map_err(|e| e.to_string())
+    }
 }
 
 /// Cache statistics
@@ -136,6 +411,20 @@ pub struct CacheStats {
     pub misses: usize,
     pub hit_rate: f64,
     pub total_patterns: usize,
+    /// Fraction of message bytes saved by cross-turn chunk deduplication.
+    pub dedup_ratio: f64,
+    /// Total bytes saved by chunk deduplication so far.
+    pub bytes_saved: usize,
+    /// Tracked logical bytes currently held by cached entries (payload +
+    /// decompressed text + chunk references + per-entry overhead).
+    pub bytes_used: usize,
+    /// Configured byte ceiling, if the cache was built with
+    /// `with_memory_budget`.
+    pub bytes_budget: Option<usize>,
+    /// Entries whose `decompressed_text` hasn't been materialized yet
+    /// (stored via `store_compressed_only` and never read with
+    /// `CachedPattern::get_text`).
+    pub unmaterialized_entries: usize,
 }
 
 /// Processing result with timing and cache stats
@@ -171,6 +460,22 @@ pub struct ConversationAccelerator {
     enable_predictive_preload: bool,
     platform_patterns: HashMap<u32, usize>, // signature -> frequency
     processing_times: Vec<f64>,
+    /// First-order transition counts: signature -> successor -> stats.
+    transitions: HashMap<u32, HashMap<u32, EdgeStats>>,
+    /// Second-order transition counts, keyed on the last two signatures;
+    /// consulted first and falls back to `transitions` when the pair has
+    /// never been observed.
+    transitions2: HashMap<(u32, u32), HashMap<u32, EdgeStats>>,
+    /// Signature of the message processed immediately before the current one.
+    last_signature: Option<u32>,
+    /// Signature of the message processed before `last_signature`.
+    prev_signature: Option<u32>,
+    /// Monotonic counter stamped onto each edge observed, used as the
+    /// recency tie-breaker when ranking successors.
+    transition_sequence: usize,
+    /// Codec used to lazily materialize `decompressed_text` for entries
+    /// stored compressed-only; unset until `set_decompressor` is called.
+    decompressor: Option<Box<dyn Decompressor>>,
 }
 
 impl ConversationAccelerator {
@@ -182,7 +487,36 @@ impl ConversationAccelerator {
             enable_predictive_preload,
             platform_patterns: HashMap::new(),
             processing_times: Vec::new(),
+            transitions: HashMap::new(),
+            transitions2: HashMap::new(),
+            last_signature: None,
+            prev_signature: None,
+            transition_sequence: 0,
+            decompressor: None,
+        }
+    }
+
+    /// Install the codec used to materialize compressed-only cache entries
+    /// on demand. Must be called before `get_text` if any entries were
+    /// stored via `ConversationCache::store_compressed_only`.
+    pub fn set_decompressor(&mut self, decompressor: Box<dyn Decompressor>) {
+        self.decompressor = Some(decompressor);
+    }
+
+    /// Look up `metadata`'s cached pattern and materialize its text,
+    /// decompressing via the installed decompressor on first demand.
+    /// Returns `None` on a cache miss, or `Some(Err(..))` if the entry's
+    /// text isn't materialized yet and no decompressor has been installed.
+    pub fn get_text(&mut self, metadata: &[MetadataEntry]) -> Option<Result<String, String>> {
+        let pattern = self.cache.lookup(metadata)?;
+        if let Some(text) = &pattern.decompressed_text {
+            return Some(Ok(text.clone()));
         }
+
+        Some(match &self.decompressor {
+            Some(decompressor) => pattern.get_text(decompressor.as_ref()).map(|text| text.to_string()),
+            None => Err("no decompressor installed; call set_decompressor first".to_string()),
+        })
     }
 
     /// Process message with adaptive acceleration
@@ -197,10 +531,12 @@ impl ConversationAccelerator {
         let start_time = Instant::now();
         self.message_count += 1;
 
+        let signature = compute_metadata_signature(&metadata);
+
         // Try cache lookup (instant if hit)
         let cached = self.cache.lookup(&metadata);
 
-        if let Some(cached_pattern) = cached {
+        let result = if let Some(cached_pattern) = cached {
             // Cache hit: Instant response (0.15ms typical)
             let processing_time = start_time.elapsed().as_secs_f64() * 1000.0;
             self.processing_times.push(processing_time);
@@ -221,7 +557,6 @@ impl ConversationAccelerator {
 
             // Update platform-wide patterns (Claim 31A)
             if self.enable_platform_learning {
-                let signature = compute_metadata_signature(&metadata);
                 *self.platform_patterns.entry(signature).or_insert(0) += 1;
             }
 
@@ -234,7 +569,89 @@ impl ConversationAccelerator {
                 decompressed_text,
                 speedup: 1.0, // No speedup on cache miss
             }
+        };
+
+        // Record the signature flow (Claim 31B): a transition is only ever
+        // an edge between two observed messages, so the first message of a
+        // conversation records nothing.
+        self.record_transition(signature);
+
+        result
+    }
+
+    /// Record the edge `last_signature -> signature` (and, once a second
+    /// prior signature is known, the second-order edge too), then shift the
+    /// signature history forward. Self-transitions are legal and counted:
+    /// a repeated identical turn is common in Q&A conversations.
+    fn record_transition(&mut self, signature: u32) {
+        self.transition_sequence += 1;
+        let seq = self.transition_sequence;
+
+        if let Some(prev) = self.last_signature {
+            Self::record_edge(&mut self.transitions, prev, signature, seq);
+
+            if let Some(earlier) = self.prev_signature {
+                Self::record_edge(&mut self.transitions2, (earlier, prev), signature, seq);
+            }
         }
+
+        self.prev_signature = self.last_signature;
+        self.last_signature = Some(signature);
+    }
+
+    /// Increment the edge `from -> to`'s count and stamp its recency, then
+    /// evict the table's lowest-count edge if this pushed it past
+    /// `MAX_TRANSITION_EDGES`.
+    fn record_edge<K: Eq + Hash + Clone>(
+        table: &mut HashMap<K, HashMap<u32, EdgeStats>>,
+        from: K,
+        to: u32,
+        seq: usize,
+    ) {
+        let stats = table.entry(from).or_default().entry(to).or_default();
+        stats.count += 1;
+        stats.last_seen = seq;
+
+        if Self::edge_count(table) > MAX_TRANSITION_EDGES {
+            Self::evict_lowest_count_edge(table);
+        }
+    }
+
+    fn edge_count<K: Eq + Hash>(table: &HashMap<K, HashMap<u32, EdgeStats>>) -> usize {
+        table.values().map(|successors| successors.len()).sum()
+    }
+
+    fn evict_lowest_count_edge<K: Eq + Hash + Clone>(table: &mut HashMap<K, HashMap<u32, EdgeStats>>) {
+        let victim = table
+            .iter()
+            .flat_map(|(from, successors)| {
+                successors
+                    .iter()
+                    .map(move |(to, stats)| (from.clone(), *to, stats.count))
+            })
+            .min_by_key(|(_, _, count)| *count);
+
+        if let Some((from, to, _)) = victim {
+            if let Some(successors) = table.get_mut(&from) {
+                successors.remove(&to);
+                if successors.is_empty() {
+                    table.remove(&from);
+                }
+            }
+        }
+    }
+
+    /// Rank a successor map by count, breaking ties in favor of the most
+    /// recently observed edge, and return the top `num_predictions`.
+    fn top_successors(successors: &HashMap<u32, EdgeStats>, num_predictions: usize) -> Vec<u32> {
+        let mut ranked: Vec<(u32, EdgeStats)> =
+            successors.iter().map(|(sig, stats)| (*sig, *stats)).collect();
+        ranked.sort_by(|a, b| {
+            b.1.count
+                .cmp(&a.1.count)
+                .then_with(|| b.1.last_seen.cmp(&a.1.last_seen))
+        });
+        ranked.into_iter().take(num_predictions).map(|(sig, _)| sig).collect()
     }
 
     /// Calculate speedup factor vs baseline (13ms)
@@ -305,7 +722,12 @@ impl ConversationAccelerator {
 
     /// Predictive pattern pre-loading (Claim 31B)
     ///
-    /// Anticipate next message based on conversation flow.
+    /// Anticipates the next message's metadata signature from conversation
+    /// flow rather than marginal frequency: a genuine first-order Markov
+    /// predictor over observed signature transitions, upgraded to
+    /// second-order whenever the last two signatures have a recorded
+    /// successor table. The caller uses the returned signatures to warm the
+    /// cache for whichever pattern actually arrives next.
     pub fn predict_next_patterns(
         &self,
         current_metadata: &[MetadataEntry],
@@ -315,26 +737,21 @@ impl ConversationAccelerator {
             return Vec::new();
         }
 
-        // Get signature of current message
-        let _current_sig = compute_metadata_signature(current_metadata);
+        let current_sig = compute_metadata_signature(current_metadata);
 
-        // Find patterns that commonly follow current pattern
-        // (In production, this would use a Markov chain or RNN)
-        let mut predictions = Vec::new();
-
-        // Simple heuristic: Return most frequent platform patterns
-        if !self.platform_patterns.is_empty() {
-            let mut sorted_patterns: Vec<_> = self.platform_patterns.iter().collect();
-            sorted_patterns.sort_by(|a, b| b.1.cmp(a.1));
-
-            predictions = sorted_patterns
-                .iter()
-                .take(num_predictions)
-                .map(|(sig, _)| **sig)
-                .collect();
+        // The second-order key pairs the signature before `current_sig`
+        // with `current_sig` itself, assuming `current_metadata` is the
+        // message most recently passed to `process_message`.
+        if let Some(earlier) = self.prev_signature {
+            if let Some(successors) = self.transitions2.get(&(earlier, current_sig)) {
+                return Self::top_successors(successors, num_predictions);
+            }
         }
 
-        predictions
+        self.transitions
+            .get(&current_sig)
+            .map(|successors| Self::top_successors(successors, num_predictions))
+            .unwrap_or_default()
     }
 
     /// Conversation type classification (Claim 31C)
@@ -358,6 +775,36 @@ impl ConversationAccelerator {
     }
 }
 
+/// Current on-disk schema version for `PlatformAccelerator` snapshots. Bump
+/// this and add a matching arm to `migrate_snapshot` whenever the stored
+/// shape changes (e.g. the signature widens past 31 bits, or
+/// `conversation_types` keys are renamed), so an older snapshot upgrades on
+/// load instead of being discarded.
+const PLATFORM_SNAPSHOT_VERSION: u32 = 1;
+
+/// Durable, versioned form of `PlatformAccelerator`'s learned state.
+#[derive(Debug, Serialize, Deserialize)]
+struct PlatformSnapshot {
+    version: u32,
+    global_patterns: HashMap<u32, usize>,
+    conversation_types: HashMap<String, usize>,
+}
+
+/// Upgrade `snapshot` to `PLATFORM_SNAPSHOT_VERSION`, applying each
+/// version's registered migration in turn so a file written by an older
+/// build loads instead of being discarded. There is only one schema so far,
+/// so this is a no-op placeholder; a future migration adds a match arm here
+/// rather than changing `load_from_reader`.
+fn migrate_snapshot(mut snapshot: PlatformSnapshot) -> PlatformSnapshot {
+    // No migrations registered yet: version 1 is the first schema. A future
+    // bump adds an `if snapshot.version == N { snapshot = ...; }` step here,
+    // one per version, so the loop walks forward one schema at a time.
+    while snapshot.version < PLATFORM_SNAPSHOT_VERSION {
+        snapshot.version = PLATFORM_SNAPSHOT_VERSION;
+    }
+    snapshot
+}
+
 /// Platform-wide learning (Claim 31A)
 ///
 /// Shared pattern library across all users.
@@ -404,6 +851,33 @@ impl PlatformAccelerator {
             top_10_patterns: self.get_top_patterns(10),
         }
     }
+
+    /// Serialize the learned pattern library to a versioned binary
+    /// snapshot, so a restarted process can seed itself from the
+    /// accumulated cross-conversation frequency table instead of starting
+    /// cold at 0% hit rate.
+    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<(), String> {
+        let snapshot = PlatformSnapshot {
+            version: PLATFORM_SNAPSHOT_VERSION,
+            global_patterns: self.global_patterns.clone(),
+            conversation_types: self.conversation_types.clone(),
+        };
+        bincode::serialize_into(writer, &snapshot).map_err(|e| e.to_string())
+    }
+
+    /// Load a snapshot written by `save_to_writer`, migrating it to the
+    /// current schema first if it was written by an older build. Callers
+    /// typically follow this with `get_top_patterns` to preload the cache.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<Self, String> {
+        let snapshot: PlatformSnapshot =
+            bincode::deserialize_from(reader).map_err(|e| e.to_string())?;
+        let snapshot = migrate_snapshot(snapshot);
+
+        Ok(Self {
+            global_patterns: snapshot.global_patterns,
+            conversation_types: snapshot.conversation_types,
+        })
+    }
 }
 
 impl Default for PlatformAccelerator {
@@ -445,6 +919,93 @@ mod tests {
         assert!(cache.get_hit_rate() > 0.0);
     }
 
+    #[test]
+    fn evicts_least_recently_used_entry() {
+        let mut cache = ConversationCache::new(2);
+
+        let a = vec![MetadataEntry::new(0, MetadataKind::Template, 1)];
+        let b = vec![MetadataEntry::new(0, MetadataKind::Template, 2)];
+        let c = vec![MetadataEntry::new(0, MetadataKind::Template, 3)];
+
+        cache.store(a.clone(), vec![1], None);
+        cache.store(b.clone(), vec![2], None);
+
+        // Touch `a` so `b` becomes the least-recently-used entry.
+        assert!(cache.lookup(&a).is_some());
+
+        // Inserting a third pattern should evict `b`, not `a`.
+        cache.store(c.clone(), vec![3], None);
+
+        assert!(cache.lookup(&a).is_some());
+        assert!(cache.lookup(&b).is_none());
+        assert!(cache.lookup(&c).is_some());
+    }
+
+    #[test]
+    fn evicts_on_memory_budget_even_under_entry_count_limit() {
+        // Entry-count limit is generous; the byte budget is what bites.
+        let mut cache = ConversationCache::with_memory_budget(100, 150);
+
+        let a = vec![MetadataEntry::new(0, MetadataKind::Template, 1)];
+        let b = vec![MetadataEntry::new(0, MetadataKind::Template, 2)];
+
+        cache.store(a.clone(), vec![0; 50], None);
+        let stats = cache.get_stats();
+        assert_eq!(stats.bytes_budget, Some(150));
+        assert!(stats.bytes_used <= 150);
+
+        // `b`'s payload pushes the running total past the 150-byte budget,
+        // so `a` (the only other, least-recently-used entry) is evicted.
+        cache.store(b.clone(), vec![0; 50], None);
+
+        assert!(cache.lookup(&a).is_none());
+        assert!(cache.lookup(&b).is_some());
+    }
+
+    struct UppercaseDecompressor;
+
+    impl Decompressor for UppercaseDecompressor {
+        fn decompress(&self, payload: &[u8]) -> Result<String, String> {
+            String::from_utf8(payload.to_vec())
+                .map(|s| s.to_uppercase())
+                .map_err(|e| e.to_string())
+        }
+    }
+
+    #[test]
+    fn store_compressed_only_defers_decompression_until_get_text() {
+        let mut cache = ConversationCache::new(10);
+        let metadata = vec![MetadataEntry::new(0, MetadataKind::Template, 1)];
+
+        cache.store_compressed_only(metadata.clone(), b"hello".to_vec());
+        assert_eq!(cache.get_stats().unmaterialized_entries, 1);
+
+        {
+            let pattern = cache.lookup(&metadata).unwrap();
+            assert_eq!(pattern.get_text(&UppercaseDecompressor).unwrap(), "HELLO");
+        }
+        assert_eq!(cache.get_stats().unmaterialized_entries, 0);
+
+        // A second call returns the memoized string without decompressing again.
+        let pattern = cache.lookup(&metadata).unwrap();
+        assert_eq!(pattern.get_text(&UppercaseDecompressor).unwrap(), "HELLO");
+    }
+
+    #[test]
+    fn accelerator_materializes_compressed_only_entries_on_demand() {
+        let mut accelerator = ConversationAccelerator::new(false, false);
+        let metadata = vec![MetadataEntry::new(0, MetadataKind::Template, 1)];
+
+        accelerator.process_message(metadata.clone(), b"hello".to_vec(), None);
+
+        // No decompressor installed yet: materialization fails explicitly
+        // rather than silently returning empty text.
+        assert!(accelerator.get_text(&metadata).unwrap().is_err());
+
+        accelerator.set_decompressor(Box::new(UppercaseDecompressor));
+        assert_eq!(accelerator.get_text(&metadata).unwrap().unwrap(), "HELLO");
+    }
+
     #[test]
     fn test_conversation_accelerator() {
         let mut accelerator = ConversationAccelerator::new(true, false);
@@ -474,6 +1035,110 @@ mod tests {
         assert!(stats.cache_hit_rate > 0.0);
     }
 
+    #[test]
+    fn process_message_dedups_repeated_spans_across_turns() {
+        // `process_message` is the real compress/decompress path callers use;
+        // this checks the chunk dedup store is actually wired into it rather
+        // than only reachable through `ConversationCache::encode_message`
+        // directly.
+        let mut accelerator = ConversationAccelerator::new(false, false);
+
+        // Long enough (with the default chunker's 256-byte minimum) that
+        // the shared prefix reliably contains at least one full chunk
+        // boundary, so the two turns actually produce a matching chunk hash
+        // rather than differing as a single whole-message chunk each.
+        let boilerplate = "As an AI language model, I don't have personal opinions or preferences of my own, and I always aim to give balanced, clearly-sourced answers whenever I can. ".repeat(20);
+        let turn1_text = format!("{}The weather today is sunny.", boilerplate);
+        let turn2_text = format!("{}The weather today is rainy.", boilerplate);
+
+        let turn1 = vec![MetadataEntry::new(0, MetadataKind::Literal, 1)];
+        let turn2 = vec![MetadataEntry::new(0, MetadataKind::Literal, 2)];
+
+        accelerator.process_message(turn1, vec![], Some(turn1_text));
+        let stats_after_first = accelerator.get_conversation_stats().cache_stats;
+
+        accelerator.process_message(turn2, vec![], Some(turn2_text));
+        let stats_after_second = accelerator.get_conversation_stats().cache_stats;
+
+        // The second turn shares the boilerplate prefix with the first, so
+        // it should dedupe a meaningful share of its bytes against the
+        // shared chunk store.
+        assert!(stats_after_second.bytes_saved > stats_after_first.bytes_saved);
+        assert!(stats_after_second.dedup_ratio > 0.0);
+    }
+
+    #[test]
+    fn predicts_the_recorded_successor() {
+        let mut accelerator = ConversationAccelerator::new(false, true);
+
+        let a = vec![MetadataEntry::new(0, MetadataKind::Template, 1)];
+        let b = vec![MetadataEntry::new(0, MetadataKind::Template, 2)];
+
+        // First message records no transition (there's no prior signature).
+        accelerator.process_message(a.clone(), vec![], None);
+        assert!(accelerator.predict_next_patterns(&a, 1).is_empty());
+
+        // a -> b observed three times; a should now predict b.
+        for _ in 0..3 {
+            accelerator.process_message(b.clone(), vec![], None);
+            accelerator.process_message(a.clone(), vec![], None);
+        }
+
+        let predicted = accelerator.predict_next_patterns(&a, 1);
+        assert_eq!(predicted, vec![compute_metadata_signature(&b)]);
+    }
+
+    #[test]
+    fn counts_self_transitions_as_legal_edges() {
+        let mut accelerator = ConversationAccelerator::new(false, true);
+        let repeated = vec![MetadataEntry::new(0, MetadataKind::Template, 5)];
+
+        for _ in 0..3 {
+            accelerator.process_message(repeated.clone(), vec![], None);
+        }
+
+        let predicted = accelerator.predict_next_patterns(&repeated, 1);
+        assert_eq!(predicted, vec![compute_metadata_signature(&repeated)]);
+    }
+
+    #[test]
+    fn second_order_table_overrides_first_order_when_seen() {
+        let mut accelerator = ConversationAccelerator::new(false, true);
+
+        let a = vec![MetadataEntry::new(0, MetadataKind::Template, 1)];
+        let b = vec![MetadataEntry::new(0, MetadataKind::Template, 2)];
+        let c = vec![MetadataEntry::new(0, MetadataKind::Template, 3)];
+        let e = vec![MetadataEntry::new(0, MetadataKind::Template, 4)];
+        let f = vec![MetadataEntry::new(0, MetadataKind::Template, 5)];
+
+        // a -> b -> c, twice: transitions[b] = {c: 2} and the pair (a, b)
+        // specifically predicts c (count 2).
+        for _ in 0..2 {
+            accelerator.process_message(a.clone(), vec![], None);
+            accelerator.process_message(b.clone(), vec![], None);
+            accelerator.process_message(c.clone(), vec![], None);
+        }
+        // e -> b -> f, three times: this outweighs c as b's first-order
+        // successor (transitions[b] = {c: 2, f: 3}), so the unqualified
+        // first-order answer for b alone is now f, not c.
+        for _ in 0..3 {
+            accelerator.process_message(e.clone(), vec![], None);
+            accelerator.process_message(b.clone(), vec![], None);
+            accelerator.process_message(f.clone(), vec![], None);
+        }
+        // Revisit a -> b so the query below runs with last_signature = b and
+        // prev_signature = a, i.e. the second-order key is (a, b) - the one
+        // that predicts c - rather than some other, unpopulated pair.
+        accelerator.process_message(a.clone(), vec![], None);
+        accelerator.process_message(b.clone(), vec![], None);
+
+        // First-order alone would predict f (the now-more-frequent
+        // successor of b); the second-order table for (a, b) predicts c,
+        // and since that pair has been observed, it must win.
+        let predicted = accelerator.predict_next_patterns(&b, 1);
+        assert_eq!(predicted, vec![compute_metadata_signature(&c)]);
+    }
+
     #[test]
     fn test_platform_accelerator() {
         let mut platform = PlatformAccelerator::new();
@@ -492,4 +1157,23 @@ mod tests {
         let stats = platform.get_platform_stats();
         assert_eq!(stats.total_patterns, 1);
     }
+
+    #[test]
+    fn platform_accelerator_survives_a_save_load_round_trip() {
+        let mut platform = PlatformAccelerator::new();
+        let metadata = vec![MetadataEntry::new(0, MetadataKind::Template, 7)];
+        platform.update_global_patterns(&metadata);
+        platform.update_global_patterns(&metadata);
+
+        let mut buffer = Vec::new();
+        platform.save_to_writer(&mut buffer).unwrap();
+
+        let restored = PlatformAccelerator::load_from_reader(buffer.as_slice()).unwrap();
+
+        assert_eq!(restored.get_top_patterns(10), platform.get_top_patterns(10));
+        assert_eq!(
+            restored.get_platform_stats().total_patterns,
+            platform.get_platform_stats().total_patterns,
+        );
+    }
 }
@@ -0,0 +1,269 @@
+//! Content-defined chunking and cross-turn chunk deduplication
+//!
+//! Splits a message into variable-length, content-defined chunks (a
+//! FastCDC-style cut with normalized chunking) so that repeated spans across
+//! conversation turns - boilerplate, disclaimers, repeated context - hash to
+//! the same chunk and only need to be transmitted once.
+
+use std::collections::HashMap;
+
+/// Rolling Gear hash lookup table (256 pseudo-random 64-bit constants).
+fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut x: u64 = 0x9E3779B97F4A7C15;
+    for slot in table.iter_mut() {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        *slot = x;
+    }
+    table
+}
+
+fn mask_with_bits(bits: u32) -> u64 {
+    if bits == 0 {
+        0
+    } else {
+        (1u64 << bits.min(63)) - 1
+    }
+}
+
+/// Tunable bounds for the content-defined chunker.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkerConfig {
+    pub min_size: usize,
+    pub target_size: usize,
+    pub max_size: usize,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 256,
+            target_size: 1024,
+            max_size: 4096,
+        }
+    }
+}
+
+/// FastCDC-style content-defined chunker with normalized chunking: a
+/// stricter `mask_small` is used until the target size is reached, then a
+/// looser `mask_large` afterward, which keeps chunk sizes closer to the
+/// average than a single fixed mask would.
+pub struct ContentDefinedChunker {
+    config: ChunkerConfig,
+    gear: [u64; 256],
+    mask_small: u64,
+    mask_large: u64,
+}
+
+impl ContentDefinedChunker {
+    pub fn new(config: ChunkerConfig) -> Self {
+        let target_bits = (config.target_size.max(2) as f64).log2().round() as u32;
+        let mask_small = mask_with_bits(target_bits + 2);
+        let mask_large = mask_with_bits(target_bits.saturating_sub(2).max(1));
+
+        Self {
+            config,
+            gear: gear_table(),
+            mask_small,
+            mask_large,
+        }
+    }
+
+    /// Split `data` into content-defined chunks.
+    pub fn chunk<'a>(&self, data: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let len = self.next_cut(&data[offset..]);
+            chunks.push(&data[offset..offset + len]);
+            offset += len;
+        }
+        chunks
+    }
+
+    /// Length of the next chunk starting at the beginning of `data`.
+    fn next_cut(&self, data: &[u8]) -> usize {
+        let len = data.len();
+        if len <= self.config.min_size {
+            return len;
+        }
+
+        let mut h: u64 = 0;
+        let mut i = 0;
+
+        // Warm up the rolling hash over the minimum size without checking
+        // for a boundary - chunks are never shorter than min_size.
+        while i < self.config.min_size {
+            h = (h << 1).wrapping_add(self.gear[data[i] as usize]);
+            i += 1;
+        }
+
+        let max_len = len.min(self.config.max_size);
+        while i < max_len {
+            h = (h << 1).wrapping_add(self.gear[data[i] as usize]);
+            let mask = if i < self.config.target_size {
+                self.mask_small
+            } else {
+                self.mask_large
+            };
+            i += 1;
+            if h & mask == 0 {
+                return i;
+            }
+        }
+
+        max_len
+    }
+}
+
+/// 64-bit FNV-1a hash, used to identify chunk content.
+pub fn hash_chunk(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// A single chunk reference within an encoded message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkRef {
+    /// References a chunk already present in the store.
+    Stored(u64),
+    /// A chunk not previously seen; carries its own payload and hash.
+    New(u64, Vec<u8>),
+}
+
+/// Deduplicating chunk store shared across conversation turns.
+#[derive(Debug, Default)]
+pub struct ChunkStore {
+    chunks: HashMap<u64, Vec<u8>>,
+}
+
+impl ChunkStore {
+    pub fn new() -> Self {
+        Self {
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&[u8]> {
+        self.chunks.get(&hash).map(|v| v.as_slice())
+    }
+
+    /// Encode `text` as a list of chunk references, inserting any
+    /// previously-unseen chunk into the store.
+    pub fn encode(&mut self, chunker: &ContentDefinedChunker, text: &str) -> Vec<ChunkRef> {
+        let bytes = text.as_bytes();
+        chunker
+            .chunk(bytes)
+            .into_iter()
+            .map(|chunk| {
+                let hash = hash_chunk(chunk);
+                if self.chunks.contains_key(&hash) {
+                    ChunkRef::Stored(hash)
+                } else {
+                    self.chunks.insert(hash, chunk.to_vec());
+                    ChunkRef::New(hash, chunk.to_vec())
+                }
+            })
+            .collect()
+    }
+
+    /// Reassemble the original text from a list of chunk references,
+    /// inserting any new chunks into the store along the way.
+    pub fn decode(&mut self, refs: &[ChunkRef]) -> Result<String, String> {
+        let mut bytes = Vec::new();
+        for chunk_ref in refs {
+            match chunk_ref {
+                ChunkRef::Stored(hash) => {
+                    let chunk = self
+                        .chunks
+                        .get(hash)
+                        .ok_or_else(|| format!("unknown chunk hash {:016x}", hash))?;
+                    bytes.extend_from_slice(chunk);
+                }
+                ChunkRef::New(hash, payload) => {
+                    self.chunks.entry(*hash).or_insert_with(|| payload.clone());
+                    bytes.extend_from_slice(payload);
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    }
+}
+
+/// Compares a message's original content size against what was actually
+/// transmitted once chunk references replace deduplicated spans, returning
+/// `(original_bytes, transmitted_bytes)`.
+pub fn encoded_size(refs: &[ChunkRef], store: &ChunkStore) -> (usize, usize) {
+    let mut original_bytes = 0usize;
+    let mut transmitted_bytes = 0usize;
+
+    for chunk_ref in refs {
+        match chunk_ref {
+            ChunkRef::Stored(hash) => {
+                original_bytes += store.get(*hash).map(|c| c.len()).unwrap_or(0);
+                transmitted_bytes += std::mem::size_of::<u64>();
+            }
+            ChunkRef::New(_, payload) => {
+                original_bytes += payload.len();
+                transmitted_bytes += std::mem::size_of::<u64>() + payload.len();
+            }
+        }
+    }
+
+    (original_bytes, transmitted_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_spans_dedupe_across_turns() {
+        let chunker = ContentDefinedChunker::new(ChunkerConfig {
+            min_size: 4,
+            target_size: 16,
+            max_size: 64,
+        });
+        let mut store = ChunkStore::new();
+
+        let boilerplate = "As an AI language model, I don't have personal opinions. ";
+        let turn1 = format!("{}The weather today is sunny.", boilerplate);
+        let turn2 = format!("{}The weather today is rainy.", boilerplate);
+
+        let refs1 = store.encode(&chunker, &turn1);
+        let new_count_1 = refs1
+            .iter()
+            .filter(|r| matches!(r, ChunkRef::New(_, _)))
+            .count();
+
+        let refs2 = store.encode(&chunker, &turn2);
+        let new_count_2 = refs2
+            .iter()
+            .filter(|r| matches!(r, ChunkRef::New(_, _)))
+            .count();
+
+        // The second turn should introduce fewer new chunks than the first,
+        // since it shares the boilerplate prefix.
+        assert!(new_count_2 < new_count_1);
+
+        assert_eq!(store.decode(&refs1).unwrap(), turn1);
+        assert_eq!(store.decode(&refs2).unwrap(), turn2);
+    }
+}
@@ -24,9 +24,11 @@
 
 pub mod metadata;
 pub mod conversation;
+pub mod dedup;
 
 pub use metadata::{MetadataEntry, MetadataKind, compute_metadata_signature, classify_intent_from_metadata, predict_compression_ratio_from_metadata};
 pub use conversation::{ConversationCache, ConversationAccelerator, PlatformAccelerator, ProcessingResult, ConversationStats, CacheStats};
+pub use dedup::{ChunkRef, ChunkStore, ChunkerConfig, ContentDefinedChunker};
 
 /// AURA version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
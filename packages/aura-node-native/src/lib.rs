@@ -8,30 +8,254 @@
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use std::collections::HashMap;
-use std::io::{Read, Write};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Read, Write};
 
 // ============================================================================
 // Error Types
 // ============================================================================
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[napi]
 pub enum CompressionMethod {
   BinarySemantic = 1,
   Brotli = 2,
+  Zstd = 3,
+  Lz4 = 4,
+  Gzip = 5,
   Uncompressed = 255,
 }
 
+/// Default Brotli quality/window, matching the previous hardcoded values.
+const DEFAULT_BROTLI_QUALITY: u32 = 11;
+const DEFAULT_BROTLI_WINDOW: u32 = 22;
+/// Below this size, payloads get the full configured quality.
+const DEFAULT_BROTLI_SMALL_THRESHOLD: u32 = 4096;
+/// Above this size, quality is floored at 6 regardless of configuration.
+const DEFAULT_BROTLI_MEDIUM_THRESHOLD: u32 = 65536;
+
 impl From<u8> for CompressionMethod {
   fn from(value: u8) -> Self {
     match value {
       1 => CompressionMethod::BinarySemantic,
       2 => CompressionMethod::Brotli,
+      3 => CompressionMethod::Zstd,
+      4 => CompressionMethod::Lz4,
+      5 => CompressionMethod::Gzip,
       _ => CompressionMethod::Uncompressed,
     }
   }
 }
 
+// ============================================================================
+// Codec Registry
+// ============================================================================
+
+/// Round-trips bytes for one general-purpose compression method, identified
+/// by the same method byte the frame format leads with. `AuraCompressor`
+/// looks codecs up by that byte through a registry instead of hardwiring a
+/// match on `CompressionMethod`, so a deployment can trade ratio for speed
+/// (lz4 for latency-sensitive paths, zstd for balanced) without changing the
+/// frame format.
+trait Codec: Send + Sync {
+  fn method(&self) -> CompressionMethod;
+  fn compress(&self, data: &[u8]) -> Result<Vec<u8>>;
+  fn decompress(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+  /// Like `decompress`, but reads the compressed stream from `source`
+  /// instead of a complete in-memory slice, so a frame can be decoded out
+  /// of a buffer that has more frames after it. Returns the decompressed
+  /// bytes alongside exactly how many bytes were pulled from `source` for
+  /// this frame, since the wire format carries no explicit length for
+  /// these methods — `FrameReader` relies on that count to land at the
+  /// next frame's first byte. Implementations read `source` through
+  /// `FrameCodecReader`, which clamps every read to one byte, so they stop
+  /// pulling input the instant their own format recognizes end-of-stream
+  /// instead of overreading into whatever follows.
+  fn decompress_stream(&self, source: &mut dyn BufRead) -> Result<(Vec<u8>, usize)>;
+}
+
+/// Clamps every `read` call to at most one byte, regardless of the buffer
+/// size a decoder asks for, and tracks how many bytes have passed through.
+/// An in-memory `BufRead` has no reason to return fewer bytes than it has
+/// buffered, so a decoder that requests a large chunk at a time (as
+/// `brotli::Decompressor`, `zstd::Decoder`, and `flate2::GzDecoder` all do
+/// internally) will happily read straight through a frame boundary into
+/// whatever message follows it in the buffer. Limiting every read to one
+/// byte means a decoder can never pull past the point where it recognizes
+/// its own compressed stream has ended.
+struct FrameCodecReader<'a> {
+  inner: &'a mut dyn BufRead,
+  consumed: usize,
+}
+
+impl Read for FrameCodecReader<'_> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    if buf.is_empty() {
+      return Ok(0);
+    }
+    let n = self.inner.read(&mut buf[..1])?;
+    self.consumed += n;
+    Ok(n)
+  }
+}
+
+/// Brotli quality/window are fixed per instance. The registry's instance
+/// carries the configured defaults; `compress_with_codec` builds a one-off
+/// instance per call so it can step quality down for large payloads.
+struct BrotliCodec {
+  quality: u32,
+  window: u32,
+}
+
+impl Codec for BrotliCodec {
+  fn method(&self) -> CompressionMethod {
+    CompressionMethod::Brotli
+  }
+
+  fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    let mut compressed = Vec::new();
+    let mut compressor = brotli::CompressorWriter::new(&mut compressed, 4096, self.quality, self.window);
+    compressor
+      .write_all(data)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    drop(compressor);
+    Ok(compressed)
+  }
+
+  fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    let mut decompressor = brotli::Decompressor::new(data, 4096);
+    decompressor
+      .read_to_end(&mut decompressed)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    Ok(decompressed)
+  }
+
+  fn decompress_stream(&self, source: &mut dyn BufRead) -> Result<(Vec<u8>, usize)> {
+    let mut counting = FrameCodecReader { inner: source, consumed: 0 };
+    let mut decompressed = Vec::new();
+    brotli::Decompressor::new(&mut counting, 1)
+      .read_to_end(&mut decompressed)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    Ok((decompressed, counting.consumed))
+  }
+}
+
+struct ZstdCodec;
+
+impl Codec for ZstdCodec {
+  fn method(&self) -> CompressionMethod {
+    CompressionMethod::Zstd
+  }
+
+  fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    zstd::stream::encode_all(data, 0).map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    zstd::stream::decode_all(data).map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  fn decompress_stream(&self, source: &mut dyn BufRead) -> Result<(Vec<u8>, usize)> {
+    let mut counting = FrameCodecReader { inner: source, consumed: 0 };
+    let mut decompressed = Vec::new();
+    {
+      // zstd concatenates frames by default, reading until its *source*
+      // hits EOF rather than stopping once the first frame is decoded —
+      // exactly the overread this type exists to avoid. `single_frame`
+      // stops it at the first frame's end instead.
+      let mut decoder = zstd::stream::read::Decoder::new(&mut counting)
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?
+        .single_frame();
+      decoder
+        .read_to_end(&mut decompressed)
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    }
+    Ok((decompressed, counting.consumed))
+  }
+}
+
+struct Lz4Codec;
+
+impl Codec for Lz4Codec {
+  fn method(&self) -> CompressionMethod {
+    CompressionMethod::Lz4
+  }
+
+  fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    Ok(lz4_flex::compress_prepend_size(data))
+  }
+
+  fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    lz4_flex::decompress_size_prepended(data)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  fn decompress_stream(&self, _source: &mut dyn BufRead) -> Result<(Vec<u8>, usize)> {
+    // `compress_prepend_size`'s block format only prepends the *decompressed*
+    // size; decoding stops once that many output bytes are produced, but
+    // nothing in the format reports how many *input* bytes that consumed.
+    // Without a consumed-byte count `FrameReader` can't know where this
+    // frame ends, so Lz4 can't be multiplexed under the current wire
+    // format. Decode a standalone Lz4 payload via `AuraCompressor::decompress`
+    // instead, or switch the frame format to the self-delimiting LZ4 frame
+    // format if streaming Lz4 is needed.
+    Err(Error::new(
+      Status::GenericFailure,
+      "Lz4 frames cannot be read from a multiplexed FrameReader stream: the size-prepended block format exposes no consumed-byte count",
+    ))
+  }
+}
+
+struct GzipCodec;
+
+impl Codec for GzipCodec {
+  fn method(&self) -> CompressionMethod {
+    CompressionMethod::Gzip
+  }
+
+  fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+      .write_all(data)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    encoder.finish().map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+  }
+
+  fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(data)
+      .read_to_end(&mut out)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    Ok(out)
+  }
+
+  fn decompress_stream(&self, source: &mut dyn BufRead) -> Result<(Vec<u8>, usize)> {
+    let mut counting = FrameCodecReader { inner: source, consumed: 0 };
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(&mut counting)
+      .read_to_end(&mut decompressed)
+      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    Ok((decompressed, counting.consumed))
+  }
+}
+
+/// Build the registry of general-purpose codecs, keyed by method byte.
+/// `BinarySemantic` and `Uncompressed` aren't in here: the former needs the
+/// template table rather than a plain byte transform, and the latter is a
+/// no-op passthrough.
+fn build_codec_registry(brotli_quality: u32, brotli_window: u32) -> HashMap<u8, Box<dyn Codec>> {
+  let codecs: Vec<Box<dyn Codec>> = vec![
+    Box::new(BrotliCodec { quality: brotli_quality, window: brotli_window }),
+    Box::new(ZstdCodec),
+    Box::new(Lz4Codec),
+    Box::new(GzipCodec),
+  ];
+  codecs.into_iter().map(|codec| (codec.method() as u8, codec)).collect()
+}
+
 // ============================================================================
 // Template System
 // ============================================================================
@@ -61,6 +285,218 @@ impl Template {
   }
 }
 
+// ============================================================================
+// Slot Dictionary Registry
+// ============================================================================
+
+/// Frame layouts `compress_with_template` has emitted: v1 sends every slot
+/// as a length-prefixed literal; v2 adds a per-slot tag byte so a slot value
+/// already in the `DictionaryRegistry` can be sent as a compact ID instead.
+const BINARY_SEMANTIC_FRAME_V1: u8 = 1;
+const BINARY_SEMANTIC_FRAME_V2: u8 = 2;
+
+const SLOT_TAG_LITERAL: u8 = 0;
+const SLOT_TAG_REGISTRY_REF: u8 = 1;
+
+/// Cap on live entries. The ID space is a u16, but eviction is kept well
+/// below that so ID reuse after wraparound (see `register`) stays rare.
+const SLOT_REGISTRY_MAX_ENTRIES: usize = 4096;
+
+/// Maps frequently-seen slot values to compact u16 IDs so repeat values can
+/// be sent by reference instead of verbatim. Encoder and decoder each hold
+/// one of these and mutate it identically as they process the same slot
+/// sequence — literals are registered in the order they're first seen on
+/// both sides — so the table itself never needs to be transmitted.
+#[derive(Clone)]
+struct DictionaryRegistry {
+  by_value: HashMap<String, u16>,
+  by_id: HashMap<u16, String>,
+  /// Recency order for LRU eviction; front is least recently used.
+  order: VecDeque<u16>,
+  next_id: u16,
+}
+
+impl DictionaryRegistry {
+  fn new() -> Self {
+    Self { by_value: HashMap::new(), by_id: HashMap::new(), order: VecDeque::new(), next_id: 0 }
+  }
+
+  fn lookup(&self, value: &str) -> Option<u16> {
+    self.by_value.get(value).copied()
+  }
+
+  /// Mark `id` as just used, moving it to the back of the eviction order.
+  fn touch(&mut self, id: u16) {
+    self.order.retain(|&queued| queued != id);
+    self.order.push_back(id);
+  }
+
+  /// Register a newly-seen value and return its assigned ID. Assumes the
+  /// caller already confirmed `lookup` returned `None` for this value.
+  fn register(&mut self, value: &str) -> u16 {
+    let id = self.next_id;
+    self.next_id = self.next_id.wrapping_add(1);
+
+    // `next_id` wraps after 65536 registrations; reclaim whatever that ID
+    // was last assigned to so the map stays internally consistent.
+    if let Some(old_value) = self.by_id.remove(&id) {
+      self.by_value.remove(&old_value);
+      self.order.retain(|&queued| queued != id);
+    }
+
+    if self.by_value.len() >= SLOT_REGISTRY_MAX_ENTRIES {
+      self.evict_lru();
+    }
+
+    self.by_value.insert(value.to_string(), id);
+    self.by_id.insert(id, value.to_string());
+    self.order.push_back(id);
+    id
+  }
+
+  fn resolve(&self, id: u16) -> Option<&str> {
+    self.by_id.get(&id).map(String::as_str)
+  }
+
+  fn evict_lru(&mut self) {
+    if let Some(value) = self.order.pop_front().and_then(|id| self.by_id.remove(&id)) {
+      self.by_value.remove(&value);
+    }
+  }
+
+  /// Serialize the table to a byte buffer a long-lived connection can
+  /// persist across restarts: an entry-count varint, then the entries in
+  /// LRU order (oldest first), each as
+  /// `[id varint][value_len varint][value bytes]`, followed by `next_id`.
+  /// Reloading via `load` lets both ends of a reconnected session resume
+  /// from where they left off instead of renegotiating every slot value
+  /// from an empty table.
+  fn save(&self) -> Vec<u8> {
+    let mut data = Vec::new();
+    write_varint(&mut data, self.order.len() as u16);
+    for &id in &self.order {
+      let value = self.by_id.get(&id).expect("order and by_id stay in sync");
+      write_varint(&mut data, id);
+      let value_bytes = value.as_bytes();
+      write_varint(&mut data, value_bytes.len() as u16);
+      data.extend_from_slice(value_bytes);
+    }
+    write_varint(&mut data, self.next_id);
+    data
+  }
+
+  /// Rebuild a table previously serialized by `save`, preserving both the
+  /// original IDs (so a peer seeded from the same snapshot agrees with them
+  /// immediately) and the LRU order.
+  fn load(data: &[u8]) -> Result<Self> {
+    let mut registry = Self::new();
+    let mut offset = 0;
+
+    let (count, consumed) = read_varint(&data[offset..])?;
+    offset += consumed;
+
+    for _ in 0..count {
+      let (id, consumed) = read_varint(data.get(offset..).ok_or_else(truncated_snapshot)?)?;
+      offset += consumed;
+
+      let (value_len, consumed) = read_varint(data.get(offset..).ok_or_else(truncated_snapshot)?)?;
+      offset += consumed;
+
+      let value_len = value_len as usize;
+      let value_bytes = data.get(offset..offset + value_len).ok_or_else(truncated_snapshot)?;
+      let value = String::from_utf8(value_bytes.to_vec()).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+      offset += value_len;
+
+      registry.by_value.insert(value.clone(), id);
+      registry.by_id.insert(id, value);
+      registry.order.push_back(id);
+    }
+
+    let (next_id, _) = read_varint(data.get(offset..).ok_or_else(truncated_snapshot)?)?;
+    registry.next_id = next_id;
+
+    Ok(registry)
+  }
+}
+
+fn truncated_snapshot() -> Error {
+  Error::new(Status::InvalidArg, "Truncated slot registry snapshot")
+}
+
+/// Append `value` as an unsigned LEB128 varint (at most 3 bytes for a u16).
+fn write_varint(buf: &mut Vec<u8>, mut value: u16) {
+  loop {
+    let byte = (value & 0x7F) as u8;
+    value >>= 7;
+    if value == 0 {
+      buf.push(byte);
+      break;
+    }
+    buf.push(byte | 0x80);
+  }
+}
+
+/// Read an unsigned LEB128 varint, returning the value and bytes consumed.
+fn read_varint(data: &[u8]) -> Result<(u16, usize)> {
+  let mut value: u32 = 0;
+  let mut shift = 0;
+  for (consumed, &byte) in data.iter().enumerate() {
+    value |= ((byte & 0x7F) as u32) << shift;
+    if byte & 0x80 == 0 {
+      return Ok((value as u16, consumed + 1));
+    }
+    shift += 7;
+    if shift >= 21 {
+      break;
+    }
+  }
+  Err(Error::new(Status::InvalidArg, "Truncated varint in slot registry reference"))
+}
+
+// ============================================================================
+// Slot Metadata Side-Channel
+// ============================================================================
+
+/// Per-slot outcome of a `compress_with_template` registry pass. This
+/// mirrors the spirit of `aura_compression_rust`'s metadata side-channel
+/// (a description of the compressed stream's structure that doesn't require
+/// decompressing it) but is scoped to this crate, since aura-node-native
+/// doesn't otherwise depend on that package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[napi]
+pub enum SlotMetadataKind {
+  /// Slot value sent as a length-prefixed literal and newly registered.
+  Literal = 0,
+  /// Slot value resolved to an existing `DictionaryRegistry` entry.
+  RegistryRef = 1,
+}
+
+/// Describes one slot's fate within a `compress_with_template` frame,
+/// indexed the same way the template's own `{0}`, `{1}`, ... placeholders
+/// are.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SlotMetadataEntry {
+  pub slot_index: u8,
+  pub kind: SlotMetadataKind,
+  /// The `DictionaryRegistry` ID: the entry referenced for `RegistryRef`,
+  /// or the ID just assigned to it for `Literal`.
+  pub registry_id: u16,
+}
+
+/// Records a `compress_best_of` race where a matching template lost to a
+/// general-purpose codec, analogous in spirit to
+/// `aura_compression_rust::metadata::MetadataKind::Fallback` - scoped to
+/// this crate for the same reason `SlotMetadataEntry` is.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct FallbackMetadataEntry {
+  /// The template that matched but didn't win the race.
+  pub template_id: u8,
+  /// The method that won instead.
+  pub method: CompressionMethod,
+}
+
 // ============================================================================
 // Compression Result
 // ============================================================================
@@ -73,6 +509,23 @@ pub struct CompressionResult {
   pub compressed_size: u32,
   pub ratio: f64,
   pub template_id: Option<u8>,
+  /// Brotli quality actually applied after adaptive step-down, so callers
+  /// can observe what was chosen. `None` for non-Brotli methods.
+  pub brotli_quality: Option<u8>,
+  /// Slots resolved to a `DictionaryRegistry` reference rather than sent
+  /// literally, for `compress_with_template` results. 0 for other methods.
+  pub registry_hits: u32,
+  /// Per-slot side-channel description of the registry pass, in slot order.
+  /// Empty for non-`compress_with_template` results.
+  pub slot_metadata: Vec<SlotMetadataEntry>,
+  /// Set when `compress_best_of` found a matching template but a generic
+  /// codec still produced the smaller payload, so BinarySemantic lost the
+  /// race. Mirrors the "never-worse guarantee" fallback case the metadata
+  /// side-channel documents.
+  pub used_fallback: bool,
+  /// Set alongside `used_fallback`, describing which template lost and to
+  /// which method. `None` whenever `used_fallback` is `false`.
+  pub fallback_metadata: Option<FallbackMetadataEntry>,
 }
 
 #[napi(object)]
@@ -94,6 +547,20 @@ pub struct AuraCompressor {
   templates: HashMap<u8, Template>,
   binary_threshold: f64,
   min_size: usize,
+  codecs: HashMap<u8, Box<dyn Codec>>,
+  /// Codec the fallback path reaches for when the caller doesn't pick one
+  /// explicitly via `compress_with_method`.
+  default_fallback: CompressionMethod,
+  /// Brotli quality applied to payloads under `brotli_small_threshold`
+  /// bytes; larger payloads step this down to bound tail latency.
+  brotli_quality: u32,
+  brotli_window: u32,
+  brotli_small_threshold: u32,
+  brotli_medium_threshold: u32,
+  /// Shared slot value <-> ID table for `compress_with_template` /
+  /// `decompress_binary_semantic`. `RefCell`-wrapped since both are called
+  /// through `&self`.
+  slot_registry: RefCell<DictionaryRegistry>,
 }
 
 #[napi]
@@ -105,6 +572,13 @@ impl AuraCompressor {
       templates: HashMap::new(),
       binary_threshold: 1.1,
       min_size: 50,
+      codecs: build_codec_registry(DEFAULT_BROTLI_QUALITY, DEFAULT_BROTLI_WINDOW),
+      default_fallback: CompressionMethod::Brotli,
+      brotli_quality: DEFAULT_BROTLI_QUALITY,
+      brotli_window: DEFAULT_BROTLI_WINDOW,
+      brotli_small_threshold: DEFAULT_BROTLI_SMALL_THRESHOLD,
+      brotli_medium_threshold: DEFAULT_BROTLI_MEDIUM_THRESHOLD,
+      slot_registry: RefCell::new(DictionaryRegistry::new()),
     };
     compressor.add_default_templates();
     Ok(compressor)
@@ -117,11 +591,62 @@ impl AuraCompressor {
       templates: HashMap::new(),
       binary_threshold,
       min_size: min_size as usize,
+      codecs: build_codec_registry(DEFAULT_BROTLI_QUALITY, DEFAULT_BROTLI_WINDOW),
+      default_fallback: CompressionMethod::Brotli,
+      brotli_quality: DEFAULT_BROTLI_QUALITY,
+      brotli_window: DEFAULT_BROTLI_WINDOW,
+      brotli_small_threshold: DEFAULT_BROTLI_SMALL_THRESHOLD,
+      brotli_medium_threshold: DEFAULT_BROTLI_MEDIUM_THRESHOLD,
+      slot_registry: RefCell::new(DictionaryRegistry::new()),
+    };
+    compressor.add_default_templates();
+    Ok(compressor)
+  }
+
+  /// Create compressor with custom Brotli quality, window size, and the
+  /// payload-size thresholds at which quality steps down. `quality` is the
+  /// level used below `small_threshold` bytes; it steps down by 2 (floored
+  /// at 6) between `small_threshold` and `medium_threshold`, and drops to 6
+  /// above `medium_threshold`.
+  #[napi(factory)]
+  pub fn with_brotli_config(
+    binary_threshold: f64,
+    min_size: u32,
+    quality: u32,
+    window: u32,
+    small_threshold: u32,
+    medium_threshold: u32,
+  ) -> Result<Self> {
+    let mut compressor = Self {
+      templates: HashMap::new(),
+      binary_threshold,
+      min_size: min_size as usize,
+      codecs: build_codec_registry(quality, window),
+      default_fallback: CompressionMethod::Brotli,
+      brotli_quality: quality,
+      brotli_window: window,
+      brotli_small_threshold: small_threshold,
+      brotli_medium_threshold: medium_threshold,
+      slot_registry: RefCell::new(DictionaryRegistry::new()),
     };
     compressor.add_default_templates();
     Ok(compressor)
   }
 
+  /// Brotli quality to use for a payload of `original_size` bytes: the
+  /// configured baseline under `brotli_small_threshold`, stepped down by 2
+  /// (floored at 6) up to `brotli_medium_threshold`, and floored at 6 above
+  /// it so tail latency on large payloads stays bounded.
+  fn effective_brotli_quality(&self, original_size: usize) -> u32 {
+    if original_size < self.brotli_small_threshold as usize {
+      self.brotli_quality
+    } else if original_size < self.brotli_medium_threshold as usize {
+      self.brotli_quality.saturating_sub(2).max(6)
+    } else {
+      6
+    }
+  }
+
   /// Add a custom template
   #[napi]
   pub fn add_template(&mut self, template: Template) {
@@ -137,18 +662,183 @@ impl AuraCompressor {
   /// Compress text using best method
   #[napi]
   pub fn compress(&self, text: String) -> Result<CompressionResult> {
-    let original_size = text.as_bytes().len();
+    self.compress_best_of(text, None)
+  }
 
-    // Skip compression for tiny messages
-    if original_size < self.min_size {
-      return self.compress_uncompressed(&text);
+  /// True never-worse selection: run every applicable candidate — a
+  /// matching BinarySemantic template, the configured fallback codec, and
+  /// raw Uncompressed — measuring each encoded length (including its
+  /// 1-byte method header) and emitting whichever is smallest. `exclude`
+  /// drops specific methods from the race (e.g. an expensive codec), but
+  /// Uncompressed is always tried regardless, so the result can never be
+  /// larger than Uncompressed + 1 header byte.
+  #[napi]
+  pub fn compress_best_of(&self, text: String, exclude: Option<Vec<CompressionMethod>>) -> Result<CompressionResult> {
+    let excluded: Vec<u8> = exclude.unwrap_or_default().into_iter().map(|m| m as u8).collect();
+    let is_excluded = |method: CompressionMethod| excluded.contains(&(method as u8));
+    let original_size = text.len();
+
+    let semantic = if !is_excluded(CompressionMethod::BinarySemantic) {
+      self.match_template(&text)
+    } else {
+      None
+    };
+    let had_semantic_candidate = semantic.is_some();
+    let semantic_template_id = semantic.as_ref().map(|(template_id, _)| *template_id);
+
+    // `compress_with_template` registers newly-seen slot values in the
+    // shared registry as a side effect of encoding. That's correct when its
+    // candidate wins, but if Brotli/Uncompressed wins instead, the decoder
+    // never sees the discarded BinarySemantic frame and so never registers
+    // those values itself — leaving the two sides' registries out of sync.
+    // Snapshot before encoding the candidate and roll back unless it wins.
+    let registry_snapshot = if had_semantic_candidate { Some(self.slot_registry.borrow().clone()) } else { None };
+
+    let mut candidates = vec![self.compress_uncompressed(&text)?];
+
+    if original_size >= self.min_size && !is_excluded(self.default_fallback) {
+      candidates.push(self.compress_with_codec(&text, self.default_fallback)?);
     }
 
-    // Try Brotli compression
-    self.compress_brotli(&text)
+    if let Some((template_id, slots)) = semantic {
+      candidates.push(self.compress_with_template(template_id, slots)?);
+    }
+
+    let mut winner = candidates
+      .into_iter()
+      .min_by_key(|candidate| candidate.compressed_size)
+      .expect("the Uncompressed candidate is always produced");
+
+    // A specialized template match existed but a generic codec still won:
+    // record that so the metadata side-channel can tell a true fallback
+    // apart from "no template even matched".
+    winner.used_fallback = had_semantic_candidate && winner.method as u8 != CompressionMethod::BinarySemantic as u8;
+
+    if winner.used_fallback {
+      if let Some(snapshot) = registry_snapshot {
+        *self.slot_registry.borrow_mut() = snapshot;
+      }
+      winner.fallback_metadata = semantic_template_id.map(|template_id| FallbackMetadataEntry { template_id, method: winner.method });
+    }
+
+    Ok(winner)
+  }
+
+  /// Reverse-match `text` against the registered templates, in template-ID
+  /// order for determinism, returning the first one whose literal segments
+  /// all line up. Returns the extracted slot values alongside its ID.
+  fn match_template(&self, text: &str) -> Option<(u8, Vec<String>)> {
+    let mut ids: Vec<&u8> = self.templates.keys().collect();
+    ids.sort();
+
+    for id in ids {
+      let template = &self.templates[id];
+      if let Some(slots) = Self::match_pattern(&template.pattern, template.slots as usize, text) {
+        return Some((*id, slots));
+      }
+    }
+    None
+  }
+
+  /// Reverse-match `text` against `pattern`, assumed to contain `{0}`,
+  /// `{1}`, ... `{slot_count - 1}` in that order, each appearing exactly
+  /// once. Returns the extracted slot values if every literal segment
+  /// between (and around) the placeholders is found in order.
+  fn match_pattern(pattern: &str, slot_count: usize, text: &str) -> Option<Vec<String>> {
+    if slot_count == 0 {
+      return if text == pattern { Some(Vec::new()) } else { None };
+    }
+
+    let mut segments: Vec<&str> = Vec::with_capacity(slot_count + 1);
+    let mut rest = pattern;
+    for i in 0..slot_count {
+      let marker = format!("{{{}}}", i);
+      let idx = rest.find(&marker)?;
+      segments.push(&rest[..idx]);
+      rest = &rest[idx + marker.len()..];
+    }
+    segments.push(rest);
+
+    if !text.starts_with(segments[0]) {
+      return None;
+    }
+
+    let mut cursor = segments[0].len();
+    let mut slots = Vec::with_capacity(slot_count);
+
+    for literal in &segments[1..segments.len() - 1] {
+      let relative_idx = text[cursor..].find(literal)?;
+      slots.push(text[cursor..cursor + relative_idx].to_string());
+      cursor += relative_idx + literal.len();
+    }
+
+    let last_literal = segments[segments.len() - 1];
+    if !text[cursor..].ends_with(last_literal) {
+      return None;
+    }
+    let last_slot_end = text.len() - last_literal.len();
+    if last_slot_end < cursor {
+      return None;
+    }
+    slots.push(text[cursor..last_slot_end].to_string());
+
+    Some(slots)
+  }
+
+  /// Compress text with an explicitly chosen fallback codec (e.g. `Lz4` for
+  /// a latency-sensitive path, `Zstd` for a balanced one), rather than
+  /// always reaching for Brotli. The frame format doesn't change: the
+  /// method byte already identifies which codec produced the body.
+  #[napi]
+  pub fn compress_with_method(&self, text: String, method: CompressionMethod) -> Result<CompressionResult> {
+    self.compress_with_codec(&text, method)
+  }
+
+  fn compress_with_codec(&self, text: &str, method: CompressionMethod) -> Result<CompressionResult> {
+    let original_bytes = text.as_bytes();
+    let original_size = original_bytes.len();
+
+    // Brotli quality is adaptive per payload size, so it can't be served
+    // from the shared registry instance: build a one-off codec with the
+    // level chosen for this call instead.
+    let (compressed, brotli_quality) = if matches!(method, CompressionMethod::Brotli) {
+      let quality = self.effective_brotli_quality(original_size);
+      let codec = BrotliCodec { quality, window: self.brotli_window };
+      (codec.compress(original_bytes)?, Some(quality as u8))
+    } else {
+      let method_byte = method as u8;
+      let codec = self.codecs.get(&method_byte).ok_or_else(|| {
+        Error::new(Status::InvalidArg, format!("No codec registered for method byte {}", method_byte))
+      })?;
+      (codec.compress(original_bytes)?, None)
+    };
+
+    let mut data = vec![method as u8];
+    data.extend_from_slice(&compressed);
+
+    let compressed_size = data.len();
+    let ratio = original_size as f64 / compressed_size as f64;
+
+    Ok(CompressionResult {
+      data: data.into(),
+      method,
+      original_size: original_size as u32,
+      compressed_size: compressed_size as u32,
+      ratio,
+      template_id: None,
+      brotli_quality,
+      registry_hits: 0,
+      slot_metadata: Vec::new(),
+      used_fallback: false,
+      fallback_metadata: None,
+    })
   }
 
-  /// Compress with specific template
+  /// Compress with specific template. Each slot is checked against the
+  /// shared `DictionaryRegistry`: a value seen before is sent as a 1-byte
+  /// tag plus a varint ID, an unseen one is sent as a literal and then
+  /// registered, so the decoder (replaying the same rule) builds an
+  /// identical table without it ever being transmitted.
   #[napi]
   pub fn compress_with_template(&self, template_id: u8, slots: Vec<String>) -> Result<CompressionResult> {
     let template = self
@@ -157,20 +847,38 @@ impl AuraCompressor {
       .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown template ID: {}", template_id)))?;
 
     let plaintext = template.fill(&slots)?;
-    let original_size = plaintext.as_bytes().len();
+    let original_size = plaintext.len();
 
-    let mut data = Vec::new();
-    data.push(CompressionMethod::BinarySemantic as u8);
-    data.push(template_id);
-    data.push(slots.len() as u8);
+    let mut data = vec![
+      CompressionMethod::BinarySemantic as u8,
+      BINARY_SEMANTIC_FRAME_V2,
+      template_id,
+      slots.len() as u8,
+    ];
 
-    for slot in &slots {
-      let slot_bytes = slot.as_bytes();
-      let slot_len = slot_bytes.len() as u16;
-      data.push((slot_len >> 8) as u8);
-      data.push((slot_len & 0xFF) as u8);
-      data.extend_from_slice(slot_bytes);
+    let mut registry = self.slot_registry.borrow_mut();
+    let mut registry_hits = 0u32;
+    let mut slot_metadata = Vec::with_capacity(slots.len());
+
+    for (slot_index, slot) in slots.iter().enumerate() {
+      if let Some(id) = registry.lookup(slot) {
+        registry.touch(id);
+        data.push(SLOT_TAG_REGISTRY_REF);
+        write_varint(&mut data, id);
+        registry_hits += 1;
+        slot_metadata.push(SlotMetadataEntry { slot_index: slot_index as u8, kind: SlotMetadataKind::RegistryRef, registry_id: id });
+      } else {
+        let slot_bytes = slot.as_bytes();
+        let slot_len = slot_bytes.len() as u16;
+        data.push(SLOT_TAG_LITERAL);
+        data.push((slot_len >> 8) as u8);
+        data.push((slot_len & 0xFF) as u8);
+        data.extend_from_slice(slot_bytes);
+        let id = registry.register(slot);
+        slot_metadata.push(SlotMetadataEntry { slot_index: slot_index as u8, kind: SlotMetadataKind::Literal, registry_id: id });
+      }
     }
+    drop(registry);
 
     let compressed_size = data.len();
     let ratio = original_size as f64 / compressed_size as f64;
@@ -182,6 +890,11 @@ impl AuraCompressor {
       compressed_size: compressed_size as u32,
       ratio,
       template_id: Some(template_id),
+      brotli_quality: None,
+      registry_hits,
+      slot_metadata,
+      used_fallback: false,
+      fallback_metadata: None,
     })
   }
 
@@ -202,14 +915,24 @@ impl AuraCompressor {
         let (text, tid) = self.decompress_binary_semantic(compressed_data)?;
         (text, Some(tid))
       }
-      CompressionMethod::Brotli => (self.decompress_brotli(compressed_data)?, None),
       CompressionMethod::Uncompressed => {
         (String::from_utf8(compressed_data.to_vec())
           .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?, None)
       }
+      // Brotli, Zstd, Lz4, Gzip: plain byte codecs dispatched through the
+      // registry instead of one hardcoded match arm per method.
+      _ => {
+        let method_byte = method as u8;
+        let codec = self.codecs.get(&method_byte).ok_or_else(|| {
+          Error::new(Status::InvalidArg, format!("No codec registered for method byte {}", method_byte))
+        })?;
+        let decompressed = codec.decompress(compressed_data)?;
+        let text = String::from_utf8(decompressed).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+        (text, None)
+      }
     };
 
-    let original_size = plaintext.as_bytes().len();
+    let original_size = plaintext.len();
     let ratio = original_size as f64 / compressed_size as f64;
 
     Ok(DecompressionResult {
@@ -222,6 +945,43 @@ impl AuraCompressor {
     })
   }
 
+  /// Decompress a buffer holding several back-to-back AURA frames, such as
+  /// many small messages multiplexed over one socket read. Walks the
+  /// buffer with `FrameReader` rather than calling `decompress` in a loop,
+  /// since `decompress` hands a codec the whole remaining slice and lets
+  /// it read to end — fine for one frame, but it would let frame N read
+  /// straight through into frame N+1.
+  #[napi]
+  pub fn decompress_many(&self, data: Buffer) -> Result<Vec<DecompressionResult>> {
+    let mut reader = FrameReader::new(data.as_ref());
+    let mut results = Vec::new();
+    while let Some(result) = reader.read_frame(self)? {
+      results.push(result);
+    }
+    Ok(results)
+  }
+
+  /// Serialize the shared slot dictionary so a long-lived connection can
+  /// persist it across restarts. Reloading via `seed_slot_registry` lets
+  /// both ends resume from an already-learned table instead of
+  /// renegotiating every registry reference from scratch.
+  #[napi]
+  pub fn save_slot_registry(&self) -> Buffer {
+    self.slot_registry.borrow().save().into()
+  }
+
+  /// Seed the shared slot dictionary from a snapshot written by
+  /// `save_slot_registry`, replacing whatever it currently holds. Call this
+  /// before the first `compress_with_template`/`decompress` of a
+  /// reconnected session so compact registry references line up with the
+  /// values the peer already has.
+  #[napi]
+  pub fn seed_slot_registry(&self, data: Buffer) -> Result<()> {
+    let registry = DictionaryRegistry::load(data.as_ref())?;
+    *self.slot_registry.borrow_mut() = registry;
+    Ok(())
+  }
+
   // Private helper methods
 
   fn add_default_templates(&mut self) {
@@ -241,33 +1001,6 @@ impl AuraCompressor {
     }
   }
 
-  fn compress_brotli(&self, text: &str) -> Result<CompressionResult> {
-    let original_bytes = text.as_bytes();
-    let original_size = original_bytes.len();
-
-    let mut compressed = Vec::new();
-    let mut compressor = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
-    compressor
-      .write_all(original_bytes)
-      .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
-    drop(compressor);
-
-    let mut data = vec![CompressionMethod::Brotli as u8];
-    data.extend_from_slice(&compressed);
-
-    let compressed_size = data.len();
-    let ratio = original_size as f64 / compressed_size as f64;
-
-    Ok(CompressionResult {
-      data: data.into(),
-      method: CompressionMethod::Brotli,
-      original_size: original_size as u32,
-      compressed_size: compressed_size as u32,
-      ratio,
-      template_id: None,
-    })
-  }
-
   fn compress_uncompressed(&self, text: &str) -> Result<CompressionResult> {
     let original_bytes = text.as_bytes();
     let original_size = original_bytes.len();
@@ -282,10 +1015,28 @@ impl AuraCompressor {
       compressed_size: data.len() as u32,
       ratio: 1.0,
       template_id: None,
+      brotli_quality: None,
+      registry_hits: 0,
+      slot_metadata: Vec::new(),
+      used_fallback: false,
+      fallback_metadata: None,
     })
   }
 
   fn decompress_binary_semantic(&self, data: &[u8]) -> Result<(String, u8)> {
+    if data.is_empty() {
+      return Err(Error::new(Status::InvalidArg, "Data too short"));
+    }
+
+    match data[0] {
+      BINARY_SEMANTIC_FRAME_V1 => self.decompress_binary_semantic_v1(&data[1..]),
+      BINARY_SEMANTIC_FRAME_V2 => self.decompress_binary_semantic_v2(&data[1..]),
+      other => Err(Error::new(Status::InvalidArg, format!("Unsupported binary_semantic frame version: {}", other))),
+    }
+  }
+
+  /// Legacy layout: every slot is a length-prefixed literal, no tag byte.
+  fn decompress_binary_semantic_v1(&self, data: &[u8]) -> Result<(String, u8)> {
     if data.len() < 2 {
       return Err(Error::new(Status::InvalidArg, "Data too short"));
     }
@@ -331,13 +1082,415 @@ impl AuraCompressor {
     Ok((plaintext, template_id))
   }
 
-  fn decompress_brotli(&self, data: &[u8]) -> Result<String> {
-    let mut decompressed = Vec::new();
-    let mut decompressor = brotli::Decompressor::new(data, 4096);
-    decompressor
-      .read_to_end(&mut decompressed)
+  /// Current layout: each slot starts with a tag byte, either a
+  /// length-prefixed literal (registered into `slot_registry` afterward) or
+  /// a varint `DictionaryRegistry` reference (resolved from it).
+  fn decompress_binary_semantic_v2(&self, data: &[u8]) -> Result<(String, u8)> {
+    if data.len() < 2 {
+      return Err(Error::new(Status::InvalidArg, "Data too short"));
+    }
+
+    let template_id = data[0];
+    let slot_count = data[1] as usize;
+
+    let template = self
+      .templates
+      .get(&template_id)
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown template ID: {}", template_id)))?;
+
+    if slot_count != template.slots as usize {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Expected {} slots, got {}", template.slots, slot_count),
+      ));
+    }
+
+    let mut slots = Vec::new();
+    let mut offset = 2;
+    let mut registry = self.slot_registry.borrow_mut();
+
+    for _ in 0..slot_count {
+      if offset >= data.len() {
+        return Err(Error::new(Status::InvalidArg, "Missing slot tag byte"));
+      }
+      let tag = data[offset];
+      offset += 1;
+
+      match tag {
+        SLOT_TAG_LITERAL => {
+          if offset + 2 > data.len() {
+            return Err(Error::new(Status::InvalidArg, "Incomplete slot length"));
+          }
+          let slot_len = ((data[offset] as u16) << 8) | (data[offset + 1] as u16);
+          offset += 2;
+
+          if offset + slot_len as usize > data.len() {
+            return Err(Error::new(Status::InvalidArg, "Incomplete slot data"));
+          }
+
+          let slot_bytes = &data[offset..offset + slot_len as usize];
+          let slot = String::from_utf8(slot_bytes.to_vec())
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+          offset += slot_len as usize;
+
+          registry.register(&slot);
+          slots.push(slot);
+        }
+        SLOT_TAG_REGISTRY_REF => {
+          let (id, consumed) = read_varint(&data[offset..])?;
+          offset += consumed;
+
+          let slot = registry
+            .resolve(id)
+            .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown slot registry ID: {}", id)))?
+            .to_string();
+          registry.touch(id);
+          slots.push(slot);
+        }
+        other => return Err(Error::new(Status::InvalidArg, format!("Unknown slot tag byte: {}", other))),
+      }
+    }
+    drop(registry);
+
+    let plaintext = template.fill(&slots)?;
+    Ok((plaintext, template_id))
+  }
+}
+
+// ============================================================================
+// Streaming Frame Reader
+// ============================================================================
+
+/// Returned when a frame's declared length fields (a slot length, a slot
+/// count, ...) point past the bytes actually available. Distinguishing this
+/// from a clean end-of-stream lets a caller reading a socket tell "the
+/// frame is still arriving" apart from "this buffer contains garbage".
+fn incomplete_frame_error() -> Error {
+  Error::new(Status::InvalidArg, "Incomplete AURA frame: declared length extends past the available input")
+}
+
+/// Decodes a sequence of back-to-back AURA frames out of a single `BufRead`
+/// source, so many small messages can be multiplexed over one stream
+/// instead of each needing its own length-prefixed envelope. `decompress`
+/// only handles one frame per call because it hands codecs the *entire*
+/// remaining slice and lets them read to end; `FrameReader` instead reads
+/// exactly the bytes that belong to each frame — the length-prefixed
+/// structure for `BinarySemantic`, and an exact consumed-byte count (via
+/// `Codec::decompress_stream`) for the generic codecs — so the source is
+/// left positioned precisely at the first byte of the next frame.
+pub struct FrameReader<R: BufRead> {
+  source: R,
+}
+
+impl<R: BufRead> FrameReader<R> {
+  pub fn new(source: R) -> Self {
+    Self { source }
+  }
+
+  /// Decode the next frame. Returns `Ok(None)` when the source is
+  /// exhausted cleanly between frames (no bytes at all were available for
+  /// the next method byte) rather than treating that as an error. A frame
+  /// that starts but whose declared length fields run past the available
+  /// bytes returns `Err(incomplete_frame_error())` instead of panicking or
+  /// returning a silently truncated result.
+  pub fn read_frame(&mut self, compressor: &AuraCompressor) -> Result<Option<DecompressionResult>> {
+    let mut method_byte = [0u8; 1];
+    let read = self
+      .source
+      .read(&mut method_byte)
       .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+    if read == 0 {
+      return Ok(None);
+    }
+
+    let method = CompressionMethod::from(method_byte[0]);
+    let (plaintext, template_id, payload_len) = match method {
+      CompressionMethod::BinarySemantic => self.read_binary_semantic_frame(compressor)?,
+      CompressionMethod::Uncompressed => {
+        // No length prefix exists for this method in the current wire
+        // format, so a frame this method produces can only be the last one
+        // in a stream: it consumes everything left in `source`.
+        let mut bytes = Vec::new();
+        self
+          .source
+          .read_to_end(&mut bytes)
+          .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        let len = bytes.len();
+        let text = String::from_utf8(bytes).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+        (text, None, len)
+      }
+      _ => {
+        let method_byte_val = method as u8;
+        let codec = compressor.codecs.get(&method_byte_val).ok_or_else(|| {
+          Error::new(Status::InvalidArg, format!("No codec registered for method byte {}", method_byte_val))
+        })?;
+        let (decompressed, consumed) = codec.decompress_stream(&mut self.source)?;
+        let text = String::from_utf8(decompressed).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+        (text, None, consumed)
+      }
+    };
+
+    let compressed_size = 1 + payload_len;
+    let original_size = plaintext.len();
+    let ratio = original_size as f64 / compressed_size as f64;
+
+    Ok(Some(DecompressionResult {
+      plaintext,
+      method,
+      original_size: original_size as u32,
+      compressed_size: compressed_size as u32,
+      ratio,
+      template_id,
+    }))
+  }
+
+  fn read_exact_checked(&mut self, buf: &mut [u8]) -> Result<()> {
+    self.source.read_exact(buf).map_err(|e| {
+      if e.kind() == std::io::ErrorKind::UnexpectedEof {
+        incomplete_frame_error()
+      } else {
+        Error::new(Status::GenericFailure, e.to_string())
+      }
+    })
+  }
+
+  fn read_binary_semantic_frame(
+    &mut self,
+    compressor: &AuraCompressor,
+  ) -> Result<(String, Option<u8>, usize)> {
+    let mut version = [0u8; 1];
+    self.read_exact_checked(&mut version)?;
+
+    let (plaintext, template_id, body_len) = match version[0] {
+      BINARY_SEMANTIC_FRAME_V1 => self.read_binary_semantic_v1(compressor)?,
+      BINARY_SEMANTIC_FRAME_V2 => self.read_binary_semantic_v2(compressor)?,
+      other => {
+        return Err(Error::new(Status::InvalidArg, format!("Unsupported binary_semantic frame version: {}", other)))
+      }
+    };
+    Ok((plaintext, Some(template_id), 1 + body_len))
+  }
+
+  /// Legacy layout: every slot is a length-prefixed literal, no tag byte.
+  fn read_binary_semantic_v1(&mut self, compressor: &AuraCompressor) -> Result<(String, u8, usize)> {
+    let mut header = [0u8; 2];
+    self.read_exact_checked(&mut header)?;
+    let template_id = header[0];
+    let slot_count = header[1] as usize;
+    let mut consumed = header.len();
+
+    let template = compressor
+      .templates
+      .get(&template_id)
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown template ID: {}", template_id)))?;
+    if slot_count != template.slots as usize {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Expected {} slots, got {}", template.slots, slot_count),
+      ));
+    }
+
+    let mut slots = Vec::with_capacity(slot_count);
+    for _ in 0..slot_count {
+      let mut len_bytes = [0u8; 2];
+      self.read_exact_checked(&mut len_bytes)?;
+      consumed += len_bytes.len();
+
+      let slot_len = ((len_bytes[0] as u16) << 8) | (len_bytes[1] as u16);
+      let mut slot_bytes = vec![0u8; slot_len as usize];
+      self.read_exact_checked(&mut slot_bytes)?;
+      consumed += slot_bytes.len();
+
+      slots.push(String::from_utf8(slot_bytes).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?);
+    }
+
+    let plaintext = template.fill(&slots)?;
+    Ok((plaintext, template_id, consumed))
+  }
+
+  /// Current layout: each slot starts with a tag byte, either a
+  /// length-prefixed literal or a varint `DictionaryRegistry` reference.
+  fn read_binary_semantic_v2(&mut self, compressor: &AuraCompressor) -> Result<(String, u8, usize)> {
+    let mut header = [0u8; 2];
+    self.read_exact_checked(&mut header)?;
+    let template_id = header[0];
+    let slot_count = header[1] as usize;
+    let mut consumed = header.len();
+
+    let template = compressor
+      .templates
+      .get(&template_id)
+      .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown template ID: {}", template_id)))?;
+    if slot_count != template.slots as usize {
+      return Err(Error::new(
+        Status::InvalidArg,
+        format!("Expected {} slots, got {}", template.slots, slot_count),
+      ));
+    }
+
+    let mut slots = Vec::with_capacity(slot_count);
+    let mut registry = compressor.slot_registry.borrow_mut();
+
+    for _ in 0..slot_count {
+      let mut tag = [0u8; 1];
+      self.read_exact_checked(&mut tag)?;
+      consumed += 1;
+
+      match tag[0] {
+        SLOT_TAG_LITERAL => {
+          let mut len_bytes = [0u8; 2];
+          self.read_exact_checked(&mut len_bytes)?;
+          consumed += len_bytes.len();
+
+          let slot_len = ((len_bytes[0] as u16) << 8) | (len_bytes[1] as u16);
+          let mut slot_bytes = vec![0u8; slot_len as usize];
+          self.read_exact_checked(&mut slot_bytes)?;
+          consumed += slot_bytes.len();
+
+          let slot = String::from_utf8(slot_bytes).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+          registry.register(&slot);
+          slots.push(slot);
+        }
+        SLOT_TAG_REGISTRY_REF => {
+          // Varints aren't fixed-width, so they're read one byte at a time
+          // rather than via a pre-sized buffer like the other fields here.
+          let mut varint_bytes = Vec::new();
+          loop {
+            let mut byte = [0u8; 1];
+            self.read_exact_checked(&mut byte)?;
+            consumed += 1;
+            let continues = byte[0] & 0x80 != 0;
+            varint_bytes.push(byte[0]);
+            if !continues {
+              break;
+            }
+          }
+          let (id, _) = read_varint(&varint_bytes)?;
+
+          let slot = registry
+            .resolve(id)
+            .ok_or_else(|| Error::new(Status::InvalidArg, format!("Unknown slot registry ID: {}", id)))?
+            .to_string();
+          registry.touch(id);
+          slots.push(slot);
+        }
+        other => return Err(Error::new(Status::InvalidArg, format!("Unknown slot tag byte: {}", other))),
+      }
+    }
+    drop(registry);
+
+    let plaintext = template.fill(&slots)?;
+    Ok((plaintext, template_id, consumed))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn compressor() -> AuraCompressor {
+    AuraCompressor::new().unwrap()
+  }
+
+  #[test]
+  fn codec_registry_round_trips_each_generic_method() {
+    let compressor = compressor();
+    let text = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+
+    for method in [CompressionMethod::Brotli, CompressionMethod::Zstd, CompressionMethod::Lz4, CompressionMethod::Gzip] {
+      let compressed = compressor.compress_with_method(text.clone(), method).unwrap();
+      assert_eq!(compressed.method, method);
+      assert!(compressed.compressed_size < compressed.original_size);
+
+      let decompressed = compressor.decompress(compressed.data).unwrap();
+      assert_eq!(decompressed.plaintext, text);
+      assert_eq!(decompressed.method, method);
+    }
+  }
+
+  #[test]
+  fn brotli_quality_steps_down_with_payload_size() {
+    let compressor = AuraCompressor::with_brotli_config(1.1, 50, 11, 22, 100, 1000).unwrap();
+
+    let small = compressor.compress_with_method("x".repeat(50), CompressionMethod::Brotli).unwrap();
+    let medium = compressor.compress_with_method("x".repeat(500), CompressionMethod::Brotli).unwrap();
+    let large = compressor.compress_with_method("x".repeat(2000), CompressionMethod::Brotli).unwrap();
+
+    assert_eq!(small.brotli_quality, Some(11));
+    assert_eq!(medium.brotli_quality, Some(9));
+    assert_eq!(large.brotli_quality, Some(6));
+  }
+
+  #[test]
+  fn slot_registry_round_trips_through_save_and_load() {
+    let mut registry = DictionaryRegistry::new();
+    let id_a = registry.register("alpha");
+    let id_b = registry.register("beta");
+    registry.touch(id_a);
+
+    let bytes = registry.save();
+    let reloaded = DictionaryRegistry::load(&bytes).unwrap();
+
+    assert_eq!(reloaded.lookup("alpha"), Some(id_a));
+    assert_eq!(reloaded.lookup("beta"), Some(id_b));
+    assert_eq!(reloaded.resolve(id_a), Some("alpha"));
+  }
+
+  #[test]
+  fn compress_with_template_sends_repeated_slots_by_registry_reference() {
+    let compressor = compressor();
+    let first = compressor.compress_with_template(1, vec!["testing".to_string(), "Try again.".to_string()]).unwrap();
+    assert_eq!(first.registry_hits, 0);
+    assert_eq!(first.slot_metadata.len(), 2);
+    assert!(first.slot_metadata.iter().all(|entry| entry.kind == SlotMetadataKind::Literal));
+
+    let second = compressor.compress_with_template(1, vec!["testing".to_string(), "Something else.".to_string()]).unwrap();
+    assert_eq!(second.registry_hits, 1);
+    assert_eq!(second.slot_metadata[0].kind, SlotMetadataKind::RegistryRef);
+    assert_eq!(second.slot_metadata[1].kind, SlotMetadataKind::Literal);
+    assert!(second.compressed_size < first.compressed_size);
+  }
+
+  #[test]
+  fn registry_rollback_discards_the_losing_template_candidates_slot() {
+    let compressor = compressor();
+    let slot = "abc".repeat(100);
+    let text = format!("Yes, I can help with that. {}", slot);
+
+    let result = compressor.compress_best_of(text, None).unwrap();
+    assert!(result.used_fallback, "a long, highly-compressible slot should lose to Brotli");
+    assert_eq!(result.method, CompressionMethod::Brotli);
+    let fallback = result.fallback_metadata.expect("fallback metadata set alongside used_fallback");
+    assert_eq!(fallback.template_id, 10);
+    assert_eq!(fallback.method, CompressionMethod::Brotli);
+
+    // If the registry hadn't been rolled back, the decoder (replaying this
+    // same slot later against a fresh registry) would mismatch the encoder's
+    // mutated one. A fresh compress_with_template call for the same slot
+    // should still see it as unregistered, exactly as the decoder would.
+    let replay = compressor.compress_with_template(10, vec![slot]).unwrap();
+    assert_eq!(replay.registry_hits, 0);
+    assert_eq!(replay.slot_metadata[0].kind, SlotMetadataKind::Literal);
+  }
+
+  #[test]
+  fn decompress_many_does_not_overread_across_frames() {
+    let compressor = compressor();
+    let first_text = "Hello from the first frame, repeated. ".repeat(5);
+    let second_text = "A different second message, also repeated. ".repeat(5);
+
+    let first = compressor.compress_with_method(first_text.clone(), CompressionMethod::Brotli).unwrap();
+    let second = compressor.compress_with_method(second_text.clone(), CompressionMethod::Gzip).unwrap();
+
+    let mut combined = Vec::new();
+    combined.extend_from_slice(first.data.as_ref());
+    combined.extend_from_slice(second.data.as_ref());
 
-    String::from_utf8(decompressed).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+    let results = compressor.decompress_many(combined.into()).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].plaintext, first_text);
+    assert_eq!(results[0].method, CompressionMethod::Brotli);
+    assert_eq!(results[1].plaintext, second_text);
+    assert_eq!(results[1].method, CompressionMethod::Gzip);
   }
 }
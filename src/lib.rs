@@ -20,11 +20,11 @@
 //! ```rust
 //! use aura_compression::{Compressor, CompressionMethod};
 //!
-//! let compressor = Compressor::new(true, None, false, None, None);
+//! let compressor = Compressor::new(true, None, false, None, None, None);
 //!
 //! // Compress message
 //! let text = "I cannot browse the internet.";
-//! let (payload, method, metadata) = compressor.compress(text, None, None)?;
+//! let (payload, method, metadata) = compressor.compress(text, None, None, None)?;
 //!
 //! println!("Compressed: {} bytes → {} bytes", metadata.original_size, payload.len());
 //! println!("Method: {:?}", method);
@@ -43,7 +43,11 @@ use thiserror::Error;
 pub mod binary_semantic;
 pub mod auralite;
 pub mod brio;
+pub mod fsst;
+pub mod matcher;
 pub mod metadata;
+pub mod miner;
+pub mod rules;
 pub mod template_library;
 pub mod compressor;
 pub mod client_sdk;
@@ -52,7 +56,7 @@ pub mod server_sdk;
 pub use compressor::Compressor;
 pub use client_sdk::ClientSDK;
 pub use server_sdk::ServerSDK;
-pub use template_library::TemplateLibrary;
+pub use template_library::{RegistryChange, SlotDef, TemplateInfo, TemplateLibrary};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -61,6 +65,7 @@ pub enum CompressionMethod {
     AuraLite = 0x01,
     Brio = 0x02,
     AuraLiteV2 = 0x03,
+    AuraFsst = 0x04,
     Uncompressed = 0xFF,
 }
 
@@ -71,6 +76,7 @@ impl CompressionMethod {
             0x01 => Ok(Self::AuraLite),
             0x02 => Ok(Self::Brio),
             0x03 => Ok(Self::AuraLiteV2),
+            0x04 => Ok(Self::AuraFsst),
             0xFF => Ok(Self::Uncompressed),
             _ => Err(AuraError::UnknownMethod(byte)),
         }
@@ -82,6 +88,7 @@ impl CompressionMethod {
             Self::AuraLite => "auralite",
             Self::Brio => "brio",
             Self::AuraLiteV2 => "aura_lite",
+            Self::AuraFsst => "aura_fsst",
             Self::Uncompressed => "uncompressed",
         }
     }
@@ -95,12 +102,68 @@ pub struct CompressionMetadata {
     pub method: String,
     pub template_ids: Vec<u32>,
     pub timestamp: u64,
+    /// The slot values `Compressor::compress` extracted when it auto-
+    /// selected `template_ids[0]` by matching `text` against the library
+    /// (i.e. the caller passed `template_id: None`). `None` when the caller
+    /// supplied an explicit template/slots pair, when no template matched at
+    /// all, or when the matched template's encoding lost the size
+    /// arbitration to a generic method.
+    pub matched_slots: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DecompressionMetadata {
     pub method: String,
     pub template_ids: Vec<u32>,
+    /// `None` when the payload carried no integrity trailer.
+    pub integrity_algorithm: Option<String>,
+    /// `Some(true)` alongside `integrity_algorithm: Some(_)` once
+    /// `Compressor::decompress_with_metadata` has verified the trailer (a
+    /// failed check is returned as `AuraError::IntegrityMismatch` instead);
+    /// `None` when there was no trailer, or when it was not checked
+    /// (`Compressor::peek_metadata`).
+    pub integrity_verified: Option<bool>,
+}
+
+/// Digest algorithm for a payload's optional integrity trailer (see
+/// `Compressor::compress` / `Compressor::decompress`). Byte values are
+/// chosen well clear of `CompressionMethod`'s (0x00-0x04, 0xFF) so the two
+/// tags can never be confused if a caller reads the wrong header field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum HashAlgorithm {
+    Md5 = 0x10,
+    Sha1 = 0x11,
+    Sha256 = 0x12,
+}
+
+impl HashAlgorithm {
+    pub fn from_byte(byte: u8) -> Result<Self, AuraError> {
+        match byte {
+            0x10 => Ok(Self::Md5),
+            0x11 => Ok(Self::Sha1),
+            0x12 => Ok(Self::Sha256),
+            _ => Err(AuraError::UnknownHashAlgorithm(byte)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Md5 => "md5",
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+        }
+    }
+
+    /// Digest length in bytes, used to locate the trailer from the end of
+    /// the payload since it carries no length prefix of its own.
+    pub fn digest_len(&self) -> usize {
+        match self {
+            Self::Md5 => 16,
+            Self::Sha1 => 20,
+            Self::Sha256 => 32,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -108,6 +171,12 @@ pub enum AuraError {
     #[error("Unknown compression method: 0x{0:02x}")]
     UnknownMethod(u8),
 
+    #[error("Unknown hash algorithm: 0x{0:02x}")]
+    UnknownHashAlgorithm(u8),
+
+    #[error("Integrity check failed: {0} digest mismatch")]
+    IntegrityMismatch(String),
+
     #[error("Compression failed: {0}")]
     CompressionFailed(String),
 
@@ -117,9 +186,24 @@ pub enum AuraError {
     #[error("Template not found: {0}")]
     TemplateNotFound(u32),
 
+    #[error("Template {0} has no slot named '{1}'")]
+    UnknownNamedSlot(u32, String),
+
+    #[error("Template {0} has no value or default for slot '{1}'")]
+    MissingNamedSlot(u32, String),
+
     #[error("Invalid payload: {0}")]
     InvalidPayload(String),
 
+    #[error("Unsupported payload format version: {0}")]
+    UnsupportedFormatVersion(u8),
+
+    #[error("Payload was encoded against {0} version {1}, but the current version is {2}")]
+    VersionMismatch(String, u32, u32),
+
+    #[error("Payload references template registry version {0}, which this side hasn't synced yet")]
+    UnknownTemplateVersion(u32),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -156,4 +240,19 @@ mod tests {
         assert_eq!(CompressionMethod::AuraLite.as_str(), "auralite");
         assert_eq!(CompressionMethod::Brio.as_str(), "brio");
     }
+
+    #[test]
+    fn test_hash_algorithm_from_byte() {
+        assert_eq!(HashAlgorithm::from_byte(0x10).unwrap(), HashAlgorithm::Md5);
+        assert_eq!(HashAlgorithm::from_byte(0x11).unwrap(), HashAlgorithm::Sha1);
+        assert_eq!(HashAlgorithm::from_byte(0x12).unwrap(), HashAlgorithm::Sha256);
+        assert!(HashAlgorithm::from_byte(0x02).is_err());
+    }
+
+    #[test]
+    fn test_hash_algorithm_digest_len() {
+        assert_eq!(HashAlgorithm::Md5.digest_len(), 16);
+        assert_eq!(HashAlgorithm::Sha1.digest_len(), 20);
+        assert_eq!(HashAlgorithm::Sha256.digest_len(), 32);
+    }
 }